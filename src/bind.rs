@@ -0,0 +1,163 @@
+//! # Bind Mount Module
+//!
+//! This module describes host paths shared with the guest as structured
+//! values, so each mount reaches the sandbox tool as separate arguments
+//! instead of being parsed out of a flat string. [`BindSet`] is a small
+//! builder for composing many of them.
+
+use crate::USE_BWRAP;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// A host path made visible inside the guest.
+///
+/// PRoot has no read-only or optional binds: `read_only` is ignored there, and
+/// optional mounts are skipped up front when their source is missing.
+///
+/// Stored in `config.toml` as a table; omitted flags default to false.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BindMount {
+    /// Host path to share.
+    pub source: PathBuf,
+    /// Absolute path inside the guest.
+    pub dest: PathBuf,
+    /// If true, the guest cannot write through the mount (Bubblewrap only).
+    pub read_only: bool,
+    /// If true, a missing source is skipped silently instead of reported.
+    pub optional: bool,
+    /// If true, device nodes under the mount stay usable (`--dev-bind`).
+    pub device: bool,
+}
+
+/// Builder for the list of [`BindMount`]s assigned to
+/// [`crate::SandBoxConfig::binds`].
+///
+/// ```ignore
+/// let binds = BindSet::new()
+///     .ro("/etc/ssl", "/etc/ssl")
+///     .rw(home.join("projects"), "/projects")
+///     .try_ro("/usr/share/icons", "/usr/share/icons")
+///     .dev("/dev/kvm")
+///     .build()?;
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BindSet {
+    mounts: Vec<BindMount>,
+}
+
+impl BindSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a read-only bind of `src` at `dest`.
+    pub fn ro(self, src: impl Into<PathBuf>, dest: impl Into<PathBuf>) -> Self {
+        self.push(src, dest, true, false, false)
+    }
+
+    /// Adds a writable bind of `src` at `dest`.
+    pub fn rw(self, src: impl Into<PathBuf>, dest: impl Into<PathBuf>) -> Self {
+        self.push(src, dest, false, false, false)
+    }
+
+    /// Adds a read-only bind that is skipped if `src` does not exist.
+    pub fn try_ro(self, src: impl Into<PathBuf>, dest: impl Into<PathBuf>) -> Self {
+        self.push(src, dest, true, true, false)
+    }
+
+    /// Adds a writable bind that is skipped if `src` does not exist.
+    pub fn try_rw(self, src: impl Into<PathBuf>, dest: impl Into<PathBuf>) -> Self {
+        self.push(src, dest, false, true, false)
+    }
+
+    /// Adds a device node (or a directory of nodes) at the same guest path.
+    pub fn dev(self, node: impl Into<PathBuf>) -> Self {
+        let node = node.into();
+        self.push(node.clone(), node, false, false, true)
+    }
+
+    /// Checks every mount and returns the list for `SandBoxConfig::binds`.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<BindMount>)` - The mounts, in the order they were added.
+    /// * `Err` - If a source or destination is empty, or a destination is
+    ///   not absolute.
+    pub fn build(self) -> Result<Vec<BindMount>, Box<dyn Error>> {
+        for mount in &self.mounts {
+            if mount.source.as_os_str().is_empty() || mount.dest.as_os_str().is_empty() {
+                return Err(format!(
+                    "Invalid bind {} -> {}: empty path",
+                    mount.source.display(),
+                    mount.dest.display()
+                )
+                .into());
+            }
+            if !mount.dest.is_absolute() {
+                return Err(format!(
+                    "Invalid bind {} -> {}: destination must be absolute",
+                    mount.source.display(),
+                    mount.dest.display()
+                )
+                .into());
+            }
+        }
+
+        Ok(self.mounts)
+    }
+
+    /// Internal: appends a mount with the given flags.
+    fn push(
+        mut self,
+        src: impl Into<PathBuf>,
+        dest: impl Into<PathBuf>,
+        read_only: bool,
+        optional: bool,
+        device: bool,
+    ) -> Self {
+        self.mounts.push(BindMount {
+            source: src.into(),
+            dest: dest.into(),
+            read_only,
+            optional,
+            device,
+        });
+        self
+    }
+}
+
+impl BindMount {
+    /// Returns the tool arguments for this mount.
+    ///
+    /// # Arguments
+    /// * `tool` - The selected sandbox tool, which decides the bind syntax.
+    ///
+    /// # Returns
+    /// The arguments, each one a separate `argv` element.
+    pub(crate) fn to_args(&self, tool: &str) -> Vec<String> {
+        let (src, dest) = (path_arg(&self.source), path_arg(&self.dest));
+
+        match tool {
+            USE_BWRAP => {
+                let flag = match (self.device, self.read_only) {
+                    (true, _) => "--dev-bind",
+                    (false, true) => "--ro-bind",
+                    (false, false) => "--bind",
+                };
+                let flag = match self.optional {
+                    true => format!("{flag}-try"),
+                    false => flag.to_string(),
+                };
+                vec![flag, src, dest]
+            }
+            _ => vec![format!("--bind={src}:{dest}")],
+        }
+    }
+}
+
+/// Converts a path to an argument string.
+fn path_arg(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
@@ -0,0 +1,159 @@
+//! # Cgroup Module
+//!
+//! This module confines a sandbox run to a transient cgroup v2 group carrying
+//! memory and CPU limits. The group is created next to the cgroup of the
+//! current process, inside the hierarchy delegated to the user (for instance a
+//! systemd user slice), and removed once the tool has exited.
+
+use crate::macros::log_warn;
+use std::error::Error;
+use std::fs::{self, File};
+use std::os::fd::{AsRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Period of the CPU bandwidth controller, in microseconds.
+const CPU_PERIOD_US: u64 = 100_000;
+
+/// Counter making the group names unique across runs of the same process.
+static NEXT_GROUP: AtomicU64 = AtomicU64::new(0);
+
+/// A transient cgroup, removed when dropped.
+pub(crate) struct Cgroup {
+    /// Directory of the group in the cgroup v2 filesystem.
+    path: PathBuf,
+    /// The group's `cgroup.procs`, opened ahead so the child can join it
+    /// between fork and exec without allocating.
+    procs: File,
+}
+
+impl Cgroup {
+    /// Creates a group with the given limits.
+    ///
+    /// # Arguments
+    /// * `memory_limit` - Value for `memory.max`, in bytes.
+    /// * `cpu_quota` - CPU time allowed, in cores, for `cpu.max`.
+    ///
+    /// # Returns
+    /// * `Ok(Cgroup)` - The group, ready for the child to join.
+    /// * `Err` - If cgroup v2 is not mounted, the hierarchy is not delegated to
+    ///   the user, or a required controller cannot be enabled.
+    pub(crate) fn create(
+        memory_limit: Option<u64>,
+        cpu_quota: Option<f64>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let parent = delegated_parent()?;
+
+        let controllers = [
+            ("memory", memory_limit.is_some()),
+            ("cpu", cpu_quota.is_some()),
+        ];
+        let enabled = fs::read_to_string(parent.join("cgroup.subtree_control")).unwrap_or_default();
+        for (controller, _) in controllers.iter().filter(|(_, wanted)| *wanted) {
+            if enabled.split_whitespace().any(|c| c == *controller) {
+                continue;
+            }
+            fs::write(
+                parent.join("cgroup.subtree_control"),
+                format!("+{controller}"),
+            )
+            .map_err(|e| {
+                unavailable(&format!(
+                    "cannot enable the {controller} controller in {}: {e}",
+                    parent.display()
+                ))
+            })?;
+        }
+
+        let name = format!(
+            "sandbox_utils-{}-{}",
+            std::process::id(),
+            NEXT_GROUP.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = parent.join(name);
+        fs::create_dir(&path)
+            .map_err(|e| unavailable(&format!("cannot create {}: {e}", path.display())))?;
+
+        let procs = match File::options().write(true).open(path.join("cgroup.procs")) {
+            Ok(procs) => procs,
+            Err(e) => {
+                let _ = fs::remove_dir(&path);
+                return Err(unavailable(&format!("cannot join {}: {e}", path.display())));
+            }
+        };
+        let cgroup = Self { path, procs };
+
+        if let Some(bytes) = memory_limit {
+            cgroup.write("memory.max", &bytes.to_string())?;
+        }
+        if let Some(cores) = cpu_quota {
+            // The kernel rejects quotas below 1ms per period.
+            let quota = ((cores * CPU_PERIOD_US as f64).round() as u64).max(1_000);
+            cgroup.write("cpu.max", &format!("{quota} {CPU_PERIOD_US}"))?;
+        }
+
+        Ok(cgroup)
+    }
+
+    /// Returns the descriptor of `cgroup.procs`; writing `0` to it moves the
+    /// writing process into the group.
+    pub(crate) fn procs_fd(&self) -> RawFd {
+        self.procs.as_raw_fd()
+    }
+
+    /// Internal: writes a value to one of the group's interface files.
+    fn write(&self, file: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(self.path.join(file), value).map_err(|e| {
+            format!(
+                "Failed to set {file} to {value} in {}: {e}",
+                self.path.display()
+            )
+            .into()
+        })
+    }
+}
+
+impl Drop for Cgroup {
+    /// Removes the group; it is empty once every guest process has exited.
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir(&self.path) {
+            log_warn!("Failed to remove cgroup {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Finds the directory new groups are created in: the parent of the current
+/// process's cgroup, or the root when the process sits at the root.
+///
+/// A cgroup holding processes cannot enable controllers for its children, so
+/// the group is created as a sibling of the current one rather than below it.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The directory, in the cgroup v2 filesystem.
+/// * `Err` - If cgroup v2 is not mounted or the process is not in it.
+fn delegated_parent() -> Result<PathBuf, Box<dyn Error>> {
+    let mounts = fs::read_to_string("/proc/self/mounts")?;
+    let mount = mounts
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .find(|fields| fields.get(2) == Some(&"cgroup2"))
+        .and_then(|fields| fields.get(1).map(PathBuf::from))
+        .ok_or_else(|| unavailable("no cgroup2 filesystem is mounted"))?;
+
+    let cgroups = fs::read_to_string("/proc/self/cgroup")?;
+    let own = cgroups
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .ok_or_else(|| unavailable("the process is not in the cgroup v2 hierarchy"))?;
+
+    let own = mount.join(own.trim_start_matches('/'));
+    Ok(match own.as_path() == mount {
+        true => own,
+        false => own.parent().map(Path::to_path_buf).unwrap_or(own),
+    })
+}
+
+/// Builds the error returned when resource limits cannot be applied.
+fn unavailable(detail: &str) -> Box<dyn Error> {
+    format!("Resource limits need a delegated cgroup v2 hierarchy: {detail}").into()
+}
@@ -0,0 +1,289 @@
+//! # Configuration File Module
+//!
+//! This module versions the `config.toml` files written by applications built
+//! on this crate. Every file carries a top-level `version` key; older files are
+//! upgraded in place by a chain of migrations, one per version step, so renamed
+//! fields keep their values across upgrades instead of being silently dropped.
+//! [`SandBoxConfig::save`] and [`SandBoxConfig::load`] persist the user-facing
+//! part of a [`SandBoxConfig`] in that file.
+
+use crate::{BindMount, SandBoxConfig};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs};
+use toml::{Table, Value};
+
+/// Current schema version of `config.toml`.
+///
+/// Files without a `version` key are treated as version `0`.
+pub const CONFIG_VERSION: i64 = 1;
+
+/// Keys renamed between version `0` and version `1`, as `(old, new)` pairs.
+const V0_RENAMES: &[(&str, &str)] = &[("tool", "rootfs_tool")];
+
+/// Custom error type for a config file written by a newer, unknown schema.
+///
+/// Holds the version found in the file.
+#[derive(Debug)]
+pub struct UnsupportedConfigVersionError(pub i64);
+
+impl fmt::Display for UnsupportedConfigVersionError {
+    /// Formats the error message naming the found and supported versions.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Config version {} is newer than the supported version {CONFIG_VERSION}",
+            self.0
+        )
+    }
+}
+
+/// Implements the standard Error trait for UnsupportedConfigVersionError.
+impl Error for UnsupportedConfigVersionError {}
+
+/// Upgrades a parsed `config.toml` table to [`CONFIG_VERSION`].
+///
+/// # Arguments
+/// * `table` - The parsed config; modified in place.
+///
+/// # Returns
+/// * `Ok(true)` - If at least one migration was applied.
+/// * `Ok(false)` - If the table was already current.
+/// * `Err` - If `version` is not an integer, or an [`UnsupportedConfigVersionError`]
+///   if it is newer than this crate understands.
+pub fn migrate_config(table: &mut Table) -> Result<bool, Box<dyn Error>> {
+    let version = match table.get("version") {
+        None => 0,
+        Some(Value::Integer(v)) => *v,
+        Some(other) => return Err(format!("Invalid config version: {other}").into()),
+    };
+
+    if version > CONFIG_VERSION {
+        return Err(Box::new(UnsupportedConfigVersionError(version)));
+    }
+
+    if version == CONFIG_VERSION {
+        return Ok(false);
+    }
+
+    for step in version..CONFIG_VERSION {
+        match step {
+            0 => migrate_v0(table),
+            _ => return Err(format!("No migration from config version {step}").into()),
+        }
+    }
+
+    table.insert("version".into(), Value::Integer(CONFIG_VERSION));
+    Ok(true)
+}
+
+/// Reads a `config.toml`, upgrades it and writes it back if it changed.
+///
+/// The upgraded file is written to a temporary sibling and renamed over the
+/// original, so an interrupted write never leaves a truncated config.
+///
+/// # Arguments
+/// * `path` - Path to the config file, usually [`crate::config_file`].
+///
+/// # Returns
+/// * `Ok(Table)` - The parsed config, at [`CONFIG_VERSION`].
+/// * `Err` - If the file cannot be read, parsed, migrated or written.
+pub fn migrate_config_file(path: &Path) -> Result<Table, Box<dyn Error>> {
+    let mut table: Table = fs::read_to_string(path)?.parse()?;
+
+    if migrate_config(&mut table)? {
+        let tmp_path = path.with_extension("toml.tmp");
+        fs::write(&tmp_path, toml::to_string(&table)?)?;
+        fs::rename(&tmp_path, path)?;
+    }
+
+    Ok(table)
+}
+
+/// The persisted part of a [`SandBoxConfig`], as laid out in `config.toml`.
+///
+/// Every key is optional: a missing one keeps the value of
+/// [`SandBoxConfig::default`], so files written by older releases still load.
+/// Per-run settings (the command, timeout, environment) and the overlay modes
+/// are not stored. Paths are written as TOML strings, so they must be valid
+/// UTF-8.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct StoredConfig {
+    version: Option<i64>,
+    rootfs: Option<PathBuf>,
+    rootfs_tool: Option<String>,
+    args_bind: Option<String>,
+    use_root: Option<bool>,
+    ignore_extra_bind: Option<bool>,
+    use_overlay: Option<bool>,
+    overlay_as_home: Option<bool>,
+    secure_rootfs: Option<bool>,
+    copy_net_files: Option<bool>,
+    fix_mtab: Option<bool>,
+    check_bind_sources: Option<bool>,
+    share_net: Option<bool>,
+    readonly_rootfs: Option<bool>,
+    bind_gpu: Option<bool>,
+    bind_audio: Option<bool>,
+    bind_display: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shell: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    workdir: Option<PathBuf>,
+    devices: Option<Vec<PathBuf>>,
+    binds: Option<Vec<BindMount>>,
+}
+
+/// Copies the listed fields between a [`SandBoxConfig`] and a [`StoredConfig`].
+macro_rules! stored_fields {
+    ($apply:ident, $($field:ident),* $(,)?) => {
+        $( $apply!($field); )*
+    };
+}
+
+impl SandBoxConfig {
+    /// Writes the user-facing settings to a `config.toml`.
+    ///
+    /// The file is stamped with [`CONFIG_VERSION`] and written to a temporary
+    /// sibling renamed over `path`, so an interrupted write never leaves a
+    /// truncated config. Missing parent directories are created.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the config file, usually [`crate::config_file`].
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the file was written.
+    /// * `Err` - If a path is not valid UTF-8 or the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut stored = StoredConfig {
+            version: Some(CONFIG_VERSION),
+            hostname: self.hostname.clone(),
+            prompt: self.prompt.clone(),
+            user: self.user.clone(),
+            shell: self.shell.clone(),
+            workdir: self.workdir.clone(),
+            ..StoredConfig::default()
+        };
+        macro_rules! store {
+            ($field:ident) => {
+                stored.$field = Some(self.$field.clone());
+            };
+        }
+        stored_fields!(
+            store,
+            rootfs,
+            rootfs_tool,
+            args_bind,
+            use_root,
+            ignore_extra_bind,
+            use_overlay,
+            overlay_as_home,
+            secure_rootfs,
+            copy_net_files,
+            fix_mtab,
+            check_bind_sources,
+            share_net,
+            readonly_rootfs,
+            bind_gpu,
+            bind_audio,
+            bind_display,
+            devices,
+            binds,
+        );
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("toml.tmp");
+        fs::write(&tmp_path, toml::to_string(&stored)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Reads settings written by [`SandBoxConfig::save`].
+    ///
+    /// Older files are upgraded with [`migrate_config_file`] first, which also
+    /// writes the upgraded file back; keys that are missing keep their
+    /// [`SandBoxConfig::default`] value, and unknown keys are ignored.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the config file, usually [`crate::config_file`].
+    ///
+    /// # Returns
+    /// * `Ok(SandBoxConfig)` - The default configuration overlaid with the file.
+    /// * `Err` - If the file cannot be read, parsed or upgraded, a key has the
+    ///   wrong type, or it was written by a newer, unsupported version.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let table = migrate_config_file(path)?;
+        let stored: StoredConfig = Value::Table(table)
+            .try_into()
+            .map_err(|e| format!("Invalid config file {}: {e}", path.display()))?;
+
+        let mut config = SandBoxConfig {
+            hostname: stored.hostname,
+            prompt: stored.prompt,
+            user: stored.user,
+            shell: stored.shell,
+            workdir: stored.workdir,
+            ..SandBoxConfig::default()
+        };
+        macro_rules! restore {
+            ($field:ident) => {
+                if let Some(value) = stored.$field {
+                    config.$field = value;
+                }
+            };
+        }
+        stored_fields!(
+            restore,
+            rootfs,
+            rootfs_tool,
+            args_bind,
+            use_root,
+            ignore_extra_bind,
+            use_overlay,
+            overlay_as_home,
+            secure_rootfs,
+            copy_net_files,
+            fix_mtab,
+            check_bind_sources,
+            share_net,
+            readonly_rootfs,
+            bind_gpu,
+            bind_audio,
+            bind_display,
+            devices,
+            binds,
+        );
+
+        Ok(config)
+    }
+}
+
+/// Migrates an unversioned config to version `1`.
+///
+/// Applies the [`V0_RENAMES`] key renames, never overwriting a value already
+/// stored under the new name, and fills `copy_net_files`, which version `0`
+/// did not have.
+///
+/// # Arguments
+/// * `table` - The parsed config; modified in place.
+fn migrate_v0(table: &mut Table) {
+    for (old, new) in V0_RENAMES {
+        if let Some(value) = table.remove(*old) {
+            table.entry(*new).or_insert(value);
+        }
+    }
+
+    table
+        .entry("copy_net_files")
+        .or_insert(Value::Boolean(false));
+}
@@ -0,0 +1,238 @@
+//! # Rootfs Diagnosis Module
+//!
+//! This module explains the most common first-run failure: a guest `/bin/sh`
+//! that cannot be executed. It inspects the shell binary inside the rootfs
+//! (without running it) and reports a missing shell, a binary built for
+//! another architecture, or a dynamic loader absent from the rootfs.
+//!
+//! Symlinks are resolved relative to the rootfs, never to the host, so an
+//! absolute link such as `/bin -> /usr/bin` is followed inside the guest tree.
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+/// ELF program header type of the interpreter (dynamic loader) entry.
+const PT_INTERP: u32 = 3;
+
+/// Maximum number of symlinks followed while resolving a guest path.
+const MAX_SYMLINK_HOPS: usize = 40;
+
+/// Checks why the guest shell of a RootFS may fail to start.
+///
+/// Intended to run after a sandbox exited with an error, to turn an opaque
+/// exit code into a targeted hint.
+///
+/// # Arguments
+/// * `path` - The base directory given to `extract_bootstrap`; the RootFS
+///   itself is expected in its `rootfs` subdirectory.
+///
+/// # Returns
+/// * `Some(String)` - A description of the problem found.
+/// * `None` - If `/bin/sh` looks runnable (or is a script).
+pub fn diagnose_shell(path: &Path) -> Option<String> {
+    diagnose_rootfs_shell(&path.join("rootfs"), "/bin/sh")
+}
+
+/// Internal: [`diagnose_shell`] for the RootFS directory itself.
+///
+/// # Arguments
+/// * `rootfs` - The guest root directory.
+/// * `shell_path` - The guest path of the shell to inspect.
+///
+/// # Returns
+/// A description of the problem found, if any.
+pub(crate) fn diagnose_rootfs_shell(rootfs: &Path, shell_path: &str) -> Option<String> {
+    let Some(shell) = resolve_in_rootfs(rootfs, Path::new(shell_path)) else {
+        return Some(format!(
+            "the rootfs has no {shell_path} (or it is a dangling symlink)"
+        ));
+    };
+
+    let mut file = File::open(&shell).ok()?;
+    let mut header = [0u8; 64];
+    let read = file.read(&mut header).ok()?;
+
+    if read < 20 || header[..4] != *b"\x7fELF" {
+        return None;
+    }
+
+    let is_64 = header[4] == 2;
+    let little = header[5] == 1;
+    let u16_at = |b: &[u8], o: usize| {
+        let v = [b[o], b[o + 1]];
+        if little {
+            u16::from_le_bytes(v)
+        } else {
+            u16::from_be_bytes(v)
+        }
+    };
+
+    let machine = u16_at(&header, 18);
+    if let Some(host) = host_machine()
+        && machine != host
+    {
+        return Some(format!(
+            "{shell_path} is built for {}, but the host is {} (wrong rootfs architecture)",
+            machine_name(machine),
+            env::consts::ARCH
+        ));
+    }
+
+    let interp = read_interpreter(&mut file, &header, is_64, little)?;
+    if resolve_in_rootfs(rootfs, Path::new(&interp)).is_none() {
+        return Some(format!(
+            "{shell_path} needs the dynamic loader {interp}, which is missing from the rootfs"
+        ));
+    }
+
+    None
+}
+
+/// Reads the `PT_INTERP` path from the program headers of an ELF file.
+///
+/// # Arguments
+/// * `file` - The opened ELF file.
+/// * `header` - The first 64 bytes of the file.
+/// * `is_64` - Whether the file is `ELFCLASS64`.
+/// * `little` - Whether the file is little-endian.
+///
+/// # Returns
+/// The interpreter path, or `None` for static binaries and unreadable headers.
+fn read_interpreter(file: &mut File, header: &[u8], is_64: bool, little: bool) -> Option<String> {
+    let uint = |b: &[u8], o: usize, n: usize| -> u64 {
+        let bytes = &b[o..o + n];
+        let mut v = 0u64;
+        for i in 0..n {
+            let byte = if little { bytes[n - 1 - i] } else { bytes[i] };
+            v = (v << 8) | u64::from(byte);
+        }
+        v
+    };
+
+    let (phoff, phentsize, phnum) = match is_64 {
+        true => (
+            uint(header, 32, 8),
+            uint(header, 54, 2),
+            uint(header, 56, 2),
+        ),
+        false => (
+            uint(header, 28, 4),
+            uint(header, 42, 2),
+            uint(header, 44, 2),
+        ),
+    };
+
+    let mut entry = vec![0u8; phentsize as usize];
+    for i in 0..phnum {
+        file.seek(SeekFrom::Start(phoff + i * phentsize)).ok()?;
+        file.read_exact(&mut entry).ok()?;
+
+        if uint(&entry, 0, 4) as u32 != PT_INTERP {
+            continue;
+        }
+
+        let (offset, size) = match is_64 {
+            true => (uint(&entry, 8, 8), uint(&entry, 32, 8)),
+            false => (uint(&entry, 4, 4), uint(&entry, 16, 4)),
+        };
+
+        let mut interp = vec![0u8; size.min(4096) as usize];
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        file.read_exact(&mut interp).ok()?;
+
+        let end = interp.iter().position(|&b| b == 0).unwrap_or(interp.len());
+        return Some(String::from_utf8_lossy(&interp[..end]).into_owned());
+    }
+
+    None
+}
+
+/// Resolves a guest path to an existing host path, following symlinks inside
+/// the RootFS.
+///
+/// # Arguments
+/// * `rootfs` - The guest root directory.
+/// * `guest_path` - The absolute path as seen from inside the guest.
+///
+/// # Returns
+/// The host path of the final target, or `None` if it does not exist or the
+/// symlink chain is too long.
+pub(crate) fn resolve_in_rootfs(rootfs: &Path, guest_path: &Path) -> Option<PathBuf> {
+    let mut pending: Vec<PathBuf> = guest_path
+        .components()
+        .rev()
+        .filter_map(|c| match c {
+            Component::Normal(n) => Some(PathBuf::from(n)),
+            Component::ParentDir => Some(PathBuf::from("..")),
+            _ => None,
+        })
+        .collect();
+    let mut current = rootfs.to_path_buf();
+    let mut hops = 0;
+
+    while let Some(part) = pending.pop() {
+        if part == Path::new("..") {
+            if current != rootfs {
+                current.pop();
+            }
+            continue;
+        }
+
+        let next = current.join(&part);
+        let md = fs::symlink_metadata(&next).ok()?;
+
+        if !md.is_symlink() {
+            current = next;
+            continue;
+        }
+
+        hops += 1;
+        if hops > MAX_SYMLINK_HOPS {
+            return None;
+        }
+
+        let target = fs::read_link(&next).ok()?;
+        if target.is_absolute() {
+            current = rootfs.to_path_buf();
+        }
+        for c in target.components().rev() {
+            match c {
+                Component::Normal(n) => pending.push(PathBuf::from(n)),
+                Component::ParentDir => pending.push(PathBuf::from("..")),
+                _ => {}
+            }
+        }
+    }
+
+    Some(current)
+}
+
+/// Returns the ELF `e_machine` value of the host architecture, if known.
+fn host_machine() -> Option<u16> {
+    match env::consts::ARCH {
+        "x86" => Some(3),
+        "arm" => Some(40),
+        "x86_64" => Some(62),
+        "aarch64" => Some(183),
+        "riscv64" => Some(243),
+        "powerpc64" => Some(21),
+        "s390x" => Some(22),
+        _ => None,
+    }
+}
+
+/// Returns a readable name for an ELF `e_machine` value.
+fn machine_name(machine: u16) -> String {
+    match machine {
+        3 => "x86".to_string(),
+        40 => "arm".to_string(),
+        62 => "x86_64".to_string(),
+        183 => "aarch64".to_string(),
+        243 => "riscv64".to_string(),
+        21 => "powerpc64".to_string(),
+        22 => "s390x".to_string(),
+        other => format!("ELF machine {other}"),
+    }
+}
@@ -5,13 +5,23 @@
 
 use serde::Serialize;
 use serde_json::Value;
+use std::env;
 use std::error::Error;
 
 /// A visual horizontal separator line used in terminal output.
 pub const SEPARATOR: &str = "════════════════════════════════════════════════════════════";
 
+/// Smallest box width: two borders plus one character between two spaces.
+const MIN_BOX_WIDTH: usize = 5;
+
 /// Generates a formatted ASCII box containing a command.
 ///
+/// Widths are measured in characters, so multibyte commands stay aligned.
+/// Without `size`, the box grows to fit the command (50 columns minimum).
+/// With an explicit `size`, the box keeps that width (at least 5 columns) and
+/// a command that does not fit is wrapped over several lines, splitting words
+/// longer than a line.
+///
 /// # Arguments
 /// * `command` - The string slice representing the command to be displayed.
 /// * `indent` - Optional number of spaces to indent the entire box.
@@ -26,17 +36,134 @@ pub fn get_cmd_box(
     size: Option<usize>,
 ) -> Result<String, Box<dyn Error>> {
     let padding = " ".repeat(indent.unwrap_or(0));
-    let width = size.unwrap_or(50).max(command.len() + 4);
-    let inner_width = width - 2;
+    let command_width = command.chars().count();
+    let width = match size {
+        Some(size) => size.max(MIN_BOX_WIDTH),
+        None => 50.max(command_width + 4),
+    };
+    let inner_width = width.saturating_sub(2);
+    let text_width = inner_width.saturating_sub(2).max(1);
 
     let line = "═".repeat(inner_width);
     let top = format!("{padding}╔{line}╗");
     let bottom = format!("{padding}╚{line}╝");
 
-    let trailing_spaces = " ".repeat(inner_width - command.len() - 1);
-    let middle = format!("{padding}║ {command}{trailing_spaces}║");
+    let rows = match command_width <= text_width {
+        true => vec![command.to_string()],
+        false => wrap_words(command, text_width)
+            .iter()
+            .flat_map(|row| split_chars(row, text_width))
+            .collect(),
+    };
+
+    let mut cmd_box = top;
+    for row in rows {
+        let trailing_spaces = " ".repeat(inner_width.saturating_sub(row.chars().count() + 1));
+        cmd_box.push_str(&format!("\n{padding}║ {row}{trailing_spaces}║"));
+    }
+    cmd_box.push_str(&format!("\n{bottom}"));
 
-    Ok(format!("{top}\n{middle}\n{bottom}"))
+    Ok(cmd_box)
+}
+
+/// Internal helper that splits a line into chunks of at most `width` characters.
+///
+/// # Arguments
+/// * `text` - The line to split.
+/// * `width` - Maximum chunk width, in characters; must be greater than zero.
+///
+/// # Returns
+/// The chunks in order; a single empty chunk for empty input.
+fn split_chars(text: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars.chunks(width).map(|c| c.iter().collect()).collect()
+}
+
+/// Generates an aligned help text for a command and its options.
+///
+/// The usage line is drawn inside a command box (see [`get_cmd_box`]) and each
+/// option is listed below it with its description aligned in a second column.
+/// Descriptions are word-wrapped to the terminal width, read from `COLUMNS`
+/// (defaults to 80). Flags are highlighted in bold green unless `NO_COLOR` is set.
+///
+/// # Arguments
+/// * `command` - The usage line, e.g. `"alpack setup [OPTIONS]"`.
+/// * `options` - A slice of `(flag, description)` pairs.
+///
+/// # Returns
+/// * `Ok(String)` - The formatted help text.
+/// * `Err` - If formatting fails.
+pub fn get_help_text(command: &str, options: &[(&str, &str)]) -> Result<String, Box<dyn Error>> {
+    let color = env::var_os("NO_COLOR").is_none_or(|v| v.is_empty());
+    let term_width = env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse::<usize>().ok())
+        .filter(|&c| c > 0)
+        .unwrap_or(80);
+
+    let flag_width = options
+        .iter()
+        .map(|(f, _)| f.chars().count())
+        .max()
+        .unwrap_or(0);
+    let desc_indent = 4 + flag_width + 2;
+    let desc_width = term_width.saturating_sub(desc_indent).max(20);
+
+    let mut help = get_cmd_box(&format!("Usage: {command}"), Some(2), None)?;
+    help.push_str("\n\n  Options:\n");
+
+    for (flag, desc) in options {
+        let padded = format!("{flag:<flag_width$}");
+        let flag_str = if color {
+            format!("\x1b[1;32m{padded}\x1b[0m")
+        } else {
+            padded
+        };
+
+        let mut lines = wrap_words(desc, desc_width).into_iter();
+        help.push_str(&format!(
+            "    {flag_str}  {}\n",
+            lines.next().unwrap_or_default()
+        ));
+
+        for line in lines {
+            help.push_str(&format!("{}{line}\n", " ".repeat(desc_indent)));
+        }
+    }
+
+    Ok(help)
+}
+
+/// Internal helper that greedily wraps text into lines of at most `width` characters.
+///
+/// # Arguments
+/// * `text` - The text to wrap.
+/// * `width` - Maximum line width; single words longer than this are kept intact.
+///
+/// # Returns
+/// The wrapped lines, without trailing newlines.
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        let needed = current.chars().count() + word.chars().count() + 1;
+        if !current.is_empty() && needed > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
 }
 
 /// Returns a formatted error message when the rootfs directory is not found.
@@ -110,6 +237,106 @@ pub fn render_table(rows: Vec<(String, String)>) {
     println!("╚═{kw}═══╩═{vw}═══╝");
 }
 
+/// Horizontal alignment of a column in [`render_table_with`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Alignment {
+    /// Pads on the right (text columns).
+    #[default]
+    Left,
+    /// Pads on the left (sizes, counts).
+    Right,
+}
+
+/// Renders a two-column table with a header row in the terminal.
+///
+/// The header is separated from the body by a divider line. Column widths
+/// ignore ANSI color codes, so colored values (such as the output of
+/// [`get_config_diff`]) stay aligned.
+///
+/// # Arguments
+/// * `headers` - Titles of the two columns, e.g. `["Field", "Value"]`.
+/// * `rows` - A vector of tuples containing the two cells of each row.
+/// * `alignment` - Alignment of each column.
+pub fn render_table_with(
+    headers: [&str; 2],
+    rows: Vec<(String, String)>,
+    alignment: [Alignment; 2],
+) {
+    let widths = [
+        rows.iter()
+            .map(|(k, _)| visible_width(k))
+            .chain([visible_width(headers[0])])
+            .max()
+            .unwrap_or(0),
+        rows.iter()
+            .map(|(_, v)| visible_width(v))
+            .chain([visible_width(headers[1])])
+            .max()
+            .unwrap_or(0),
+    ];
+
+    let kw = "═".repeat(widths[0]);
+    let vw = "═".repeat(widths[1]);
+    let row = |cells: [&str; 2]| {
+        format!(
+            "║ {}   ║ {}   ║",
+            align_cell(cells[0], widths[0], alignment[0]),
+            align_cell(cells[1], widths[1], alignment[1])
+        )
+    };
+
+    println!("╔═{kw}═══╦═{vw}═══╗");
+    println!("{}", row(headers));
+    println!("╠═{kw}═══╬═{vw}═══╣");
+    for (k, v) in &rows {
+        println!("{}", row([k, v]));
+    }
+    println!("╚═{kw}═══╩═{vw}═══╝");
+}
+
+/// Internal helper that pads a cell to `width` visible characters.
+///
+/// # Arguments
+/// * `cell` - The cell text, possibly containing ANSI color codes.
+/// * `width` - The visible width of the column.
+/// * `alignment` - Which side receives the padding.
+///
+/// # Returns
+/// The padded cell.
+fn align_cell(cell: &str, width: usize, alignment: Alignment) -> String {
+    let pad = " ".repeat(width.saturating_sub(visible_width(cell)));
+    match alignment {
+        Alignment::Left => format!("{cell}{pad}"),
+        Alignment::Right => format!("{pad}{cell}"),
+    }
+}
+
+/// Internal helper that counts the characters of a string as shown on screen,
+/// skipping ANSI escape sequences such as `\x1b[1;31m`.
+///
+/// # Arguments
+/// * `text` - The text to measure.
+///
+/// # Returns
+/// The number of visible characters.
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
 /// Compares two serializable structures and returns a list of differences.
 ///
 /// Changed values are formatted with ANSI colors (Red for old, Green for new).
@@ -121,6 +348,62 @@ pub fn render_table(rows: Vec<(String, String)>) {
 /// # Returns
 /// A `Vec` of tuples where the first element is the field name and the second is the display value.
 pub fn get_config_diff<T: Serialize>(old: &T, new: &T) -> Vec<(String, String)> {
+    config_diff_rows(old, new, false, &[])
+}
+
+/// Compares two serializable structures and returns only the fields that changed.
+///
+/// Same row format as [`get_config_diff`], but unchanged fields are left out,
+/// so confirmation tables for large configs only show the delta.
+///
+/// # Arguments
+/// * `old` - The base configuration structure.
+/// * `new` - The updated configuration structure.
+///
+/// # Returns
+/// A `Vec` of `(field, display value)` tuples, empty if nothing changed.
+pub fn get_config_changes<T: Serialize>(old: &T, new: &T) -> Vec<(String, String)> {
+    config_diff_rows(old, new, true, &[])
+}
+
+/// Compares two serializable structures, leaving out the named fields.
+///
+/// Same row format as [`get_config_diff`], for hiding machine-specific noise
+/// such as `tool_target` from confirmation tables. Only top-level keys are
+/// matched for now; nested keys will use the dotted form (`overlay.upper`)
+/// once nested diffs are supported.
+///
+/// # Arguments
+/// * `old` - The base configuration structure.
+/// * `new` - The updated configuration structure.
+/// * `ignore` - Names of the top-level fields to skip.
+///
+/// # Returns
+/// A `Vec` of `(field, display value)` tuples for the remaining fields.
+pub fn get_config_diff_ignoring<T: Serialize>(
+    old: &T,
+    new: &T,
+    ignore: &[&str],
+) -> Vec<(String, String)> {
+    config_diff_rows(old, new, false, ignore)
+}
+
+/// Internal helper shared by the config diff functions.
+///
+/// # Arguments
+/// * `old` - The base configuration structure.
+/// * `new` - The updated configuration structure.
+/// * `changed_only` - Skips the fields whose value is identical in both.
+/// * `ignore` - Names of the top-level fields to skip.
+///
+/// # Returns
+/// A `Vec` of `(field, display value)` tuples.
+fn config_diff_rows<T: Serialize>(
+    old: &T,
+    new: &T,
+    changed_only: bool,
+    ignore: &[&str],
+) -> Vec<(String, String)> {
     let old_val = serde_json::to_value(old).unwrap_or(Value::Null);
     let new_val = serde_json::to_value(new).unwrap_or(Value::Null);
 
@@ -129,6 +412,10 @@ pub fn get_config_diff<T: Serialize>(old: &T, new: &T) -> Vec<(String, String)>
         for (key, new_v) in new_map {
             let old_v = old_val.get(&key).cloned().unwrap_or(Value::Null);
 
+            if ignore.contains(&key.as_str()) || (changed_only && old_v == new_v) {
+                continue;
+            }
+
             let new_str = json_to_display_str(&new_v);
             let old_str = json_to_display_str(&old_v);
 
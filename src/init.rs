@@ -4,14 +4,30 @@
 //! It is divided into two phases: path/architecture initialization and
 //! sandbox tool (PRoot/Bwrap) configuration.
 
-use crate::download_file;
+unsafe extern "C" {
+    /// Retrieves the real user ID of the calling process.
+    /// Used to map the host user to the sandbox environment.
+    fn getuid() -> u32;
+
+    /// Retrieves the effective user ID of the calling process.
+    /// Used to determine the current privilege level before entering the sandbox.
+    fn geteuid() -> u32;
+}
+
+use crate::{download_file_with, DownloadOptions};
 
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::env;
+use std::fmt;
 use std::fs;
+use std::io::{self, ErrorKind};
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
-use std::sync::OnceLock;
-use which::which;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, PoisonError};
+use which::which_in;
 
 /// Constant identifier for the PRoot tool.
 pub const USE_PROOT: &str = "proot";
@@ -48,55 +64,237 @@ pub struct SandboxTool {
     pub target: PathBuf,
 }
 
+/// Custom error type for [`sandbox_init`] running without a usable `HOME`.
+///
+/// Every default path derives from the home directory, so guessing one would
+/// scatter files in an arbitrary folder; [`sandbox_init_with`] takes explicit
+/// paths instead.
+#[derive(Debug)]
+pub struct HomeNotSetError;
+
+impl fmt::Display for HomeNotSetError {
+    /// Formats the error message with the way out.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "HOME is not set to an absolute path; set it or call sandbox_init_with() \
+             with explicit paths"
+        )
+    }
+}
+
+/// Implements the standard Error trait for HomeNotSetError.
+impl std::error::Error for HomeNotSetError {}
+
+/// Whether a sandbox tool can be used on this host, as reported by [`can_use`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ToolAvailability {
+    /// The name of the queried tool.
+    pub tool: String,
+    /// Path of the executable found in `PATH` or [`tool_install_dir`], if any.
+    pub installed: Option<PathBuf>,
+    /// True if a prebuilt binary can be downloaded for the current architecture.
+    pub downloadable: bool,
+    /// For Bubblewrap, whether it can create user namespaces (enabled by the
+    /// kernel and not blocked by AppArmor, or a setuid binary). `None` for
+    /// tools that do not need them.
+    pub user_namespaces: Option<bool>,
+}
+
+impl ToolAvailability {
+    /// Returns true if the tool is (or can be) installed and is expected to run.
+    pub fn usable(&self) -> bool {
+        (self.installed.is_some() || self.downloadable) && self.user_namespaces != Some(false)
+    }
+}
+
 /// Internal structure to map tool IDs to their download URLs.
 struct Link {
     id: &'static str,
-    link: &'static str,
+    /// Static binaries published for each architecture.
+    targets: &'static [Target],
 }
 
-/// List of available download links for supported tools on x86_64.
+/// A tool binary published for one architecture.
+struct Target {
+    /// Architecture name, as reported by [`app_arch`].
+    arch: &'static str,
+    url: &'static str,
+    /// Expected SHA-256 of the binary, as lowercase hex. Must be updated with
+    /// every release of the binary; while it is empty, the binary is never
+    /// downloaded.
+    sha256: &'static str,
+}
+
+impl Target {
+    /// Returns the pinned digest for [`DownloadOptions::expected_sha256`]: the
+    /// one given to [`set_tool_sha256`] if any, else the built-in one.
+    ///
+    /// # Arguments
+    /// * `sandbox_tool` - The name of the tool, for the error message.
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The digest, as lowercase hex.
+    /// * `Err` - If no digest is pinned, so an unverified executable is never
+    ///   installed.
+    fn pinned_sha256(&self, sandbox_tool: &str) -> Result<String, Box<dyn std::error::Error>> {
+        if let Some(digest) = pinned_override(sandbox_tool) {
+            return Ok(digest);
+        }
+        if self.sha256.is_empty() {
+            return Err(format!(
+                "No SHA-256 pinned for the {sandbox_tool} binary on {}; refusing to install an \
+                 unverified executable",
+                self.arch
+            )
+            .into());
+        }
+        Ok(self.sha256.to_string())
+    }
+}
+
+/// List of available download links for supported tools.
 const LINK_OPTIONS: &[Link] = &[
     Link {
         id: USE_PROOT,
-        link: "https://github.com/LinuxProativo/StaticHub/releases/download/proot/proot",
+        targets: &[Target {
+            arch: "x86_64",
+            url: "https://github.com/LinuxProativo/StaticHub/releases/download/proot/proot",
+            sha256: "",
+        }],
     },
     Link {
         id: USE_BWRAP,
-        link: "https://github.com/LinuxProativo/StaticHub/releases/download/bwrap/bwrap",
+        targets: &[Target {
+            arch: "x86_64",
+            url: "https://github.com/LinuxProativo/StaticHub/releases/download/bwrap/bwrap",
+            sha256: "",
+        }],
     },
 ];
 
+/// Ids of the entries in [`LINK_OPTIONS`], in the same order.
+const SUPPORTED_TOOLS: [&str; LINK_OPTIONS.len()] = {
+    let mut ids = [""; LINK_OPTIONS.len()];
+    let mut i = 0;
+    while i < ids.len() {
+        ids[i] = LINK_OPTIONS[i].id;
+        i += 1;
+    }
+    ids
+};
+
+/// Write-once global storage, like `OnceLock`, that the `test-util` reset
+/// helpers can clear.
+///
+/// Only the first [`Slot::set`] takes effect, and [`Slot::with`] blocks until a
+/// value is set, matching `OnceLock::set` and `OnceLock::wait`.
+struct Slot<T> {
+    value: Mutex<Option<T>>,
+    ready: Condvar,
+}
+
+impl<T: Clone> Slot<T> {
+    /// Creates an empty slot.
+    const fn new() -> Self {
+        Self {
+            value: Mutex::new(None),
+            ready: Condvar::new(),
+        }
+    }
+
+    /// Stores `value` unless the slot is already set.
+    fn set(&self, value: T) {
+        let mut slot = self.value.lock().unwrap_or_else(PoisonError::into_inner);
+        if slot.is_none() {
+            *slot = Some(value);
+            self.ready.notify_all();
+        }
+    }
+
+    /// Returns a copy of the value, if set.
+    fn get(&self) -> Option<T> {
+        self.value
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Waits until the slot is set, then applies `f` to the value.
+    fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let slot = self.value.lock().unwrap_or_else(PoisonError::into_inner);
+        let slot = self
+            .ready
+            .wait_while(slot, |v| v.is_none())
+            .unwrap_or_else(PoisonError::into_inner);
+        f(slot.as_ref().expect("slot is set"))
+    }
+
+    /// Empties the slot, so the next [`Slot::set`] takes effect again.
+    #[cfg(feature = "test-util")]
+    fn clear(&self) {
+        *self.value.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
 /// Global storage for application paths and environment config.
-static CONFIG: OnceLock<SandboxConfig> = OnceLock::new();
+static CONFIG: Slot<SandboxConfig> = Slot::new();
+
+/// Environment variable holding a mirror base URL for the tool downloads.
+///
+/// When set, for example to `https://mirror.example.org/static`, the scheme
+/// and host of each built-in URL are replaced by it and the path is kept, so
+/// the mirror must serve the same layout as GitHub:
+///
+/// ```text
+/// <mirror>/LinuxProativo/StaticHub/releases/download/proot/proot
+/// <mirror>/LinuxProativo/StaticHub/releases/download/bwrap/bwrap
+/// ...
+/// ```
+///
+/// The pinned SHA-256 digests still apply to mirrored files; a mirror serving
+/// other builds needs [`set_tool_sha256`].
+pub const TOOL_MIRROR_ENV: &str = "SANDBOX_TOOL_MIRROR";
 
 /// Global storage for the selected sandbox tool.
-static TOOL: OnceLock<SandboxTool> = OnceLock::new();
+static TOOL: Slot<SandboxTool> = Slot::new();
+
+/// Global storage for a custom tool installation directory.
+static INSTALL_DIR: Slot<PathBuf> = Slot::new();
 
-/// Target architecture for binary downloads.
-static AMD64: &str = "x86_64";
+/// Global storage for the digests given to [`set_tool_sha256`], by tool name.
+static TOOL_SHA256: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// Global storage for an explicit application display name.
+static APP_NAME: Slot<String> = Slot::new();
+
+/// Whether the rootfs path is derived per user ID (see [`resolved_rootfs`]).
+static PER_USER_ROOTFS: AtomicBool = AtomicBool::new(false);
 
 /// Initializes the base directories and detects the system architecture.
 ///
+/// The config and cache directories follow the XDG base directory spec:
+/// `$XDG_CONFIG_HOME/<name>` and `$XDG_CACHE_HOME/<name>`, falling back to
+/// `~/.config/<name>` and `~/.cache/<name>` when the variables are unset or
+/// not absolute. The default rootfs stays at `~/.<name>` either way, since it
+/// is data the user manages rather than config or disposable cache.
+///
 /// # Arguments
 /// * `name` - The internal name of the application for path generation.
 /// * `arch_env` - Environment variable name to override architecture detection.
 ///
 /// # Returns
 /// * `Ok(())` if initialization succeeds.
-/// * `Err` if directory creation fails.
-pub fn sandbox_init(
-    name: &str,
-    arch_env: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+/// * `Err` if directory creation fails, or a [`HomeNotSetError`] if `HOME` is
+///   unset, empty or relative (as in some daemon and CI contexts).
+pub fn sandbox_init(name: &str, arch_env: &str) -> Result<(), Box<dyn std::error::Error>> {
     let home = env::var_os("HOME")
         .map(PathBuf::from)
-        .unwrap_or_else(|| PathBuf::from("."));
+        .filter(|home| home.is_absolute())
+        .ok_or(HomeNotSetError)?;
 
-    let config_dir = home.join(".config").join(name);
-    fs::create_dir_all(&config_dir)?;
-
-    let default_cache = home.join(".cache").join(name);
-    fs::create_dir_all(&default_cache)?;
+    let config_dir = xdg_dir("XDG_CONFIG_HOME", &home, ".config").join(name);
+    let default_cache = xdg_dir("XDG_CACHE_HOME", &home, ".cache").join(name);
 
     let app_name = env::args_os()
         .next()
@@ -122,7 +320,44 @@ pub fn sandbox_init(
         config_dir,
     };
 
-    let _ = CONFIG.set(config);
+    sandbox_init_with(config)
+}
+
+/// Returns an XDG base directory, or its default under the home directory.
+///
+/// # Arguments
+/// * `var` - The environment variable, e.g. `XDG_CONFIG_HOME`.
+/// * `home` - The home directory.
+/// * `fallback` - The default directory name under `home`, e.g. `.config`.
+///
+/// # Returns
+/// The variable's value if it is an absolute path, as the spec requires;
+/// `home/fallback` otherwise.
+fn xdg_dir(var: &str, home: &Path, fallback: &str) -> PathBuf {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| home.join(fallback))
+}
+
+/// Initializes the global state from a fully specified path configuration.
+///
+/// Unlike [`sandbox_init`], nothing is derived from `HOME` or `argv[0]`: the
+/// given paths are stored as they are, for applications that manage their own
+/// directory layout. The config and cache directories are created if missing.
+/// Only the first initialization takes effect.
+///
+/// # Arguments
+/// * `config` - The complete path and architecture configuration.
+///
+/// # Returns
+/// * `Ok(())` if initialization succeeds.
+/// * `Err` if directory creation fails.
+pub fn sandbox_init_with(config: SandboxConfig) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(&config.config_dir)?;
+    fs::create_dir_all(&config.default_cache)?;
+
+    CONFIG.set(config);
     Ok(())
 }
 
@@ -133,43 +368,40 @@ pub fn sandbox_init(
 ///
 /// # Returns
 /// * `Ok(())` if the tool is ready for use.
-/// * `Err` if the tool is missing and cannot be downloaded for the current arch.
+/// * `Err` if the tool is missing and cannot be downloaded for the current arch,
+///   including when the binary for that arch has no pinned SHA-256.
 pub fn set_sandbox_tool(sandbox_tool: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let arch = app_arch();
     let path = env::var_os("PATH").unwrap_or_default();
-    let local_dir = safe_home().join(".local").join("bin");
+    let local_dir = tool_install_dir();
     let new_path = format!("{}:{}", path.display(), local_dir.display());
     unsafe {
         env::set_var("PATH", new_path);
     }
 
-    let tool_target = match which(sandbox_tool) {
-        Ok(target) => target,
-        Err(_) => {
-            if arch == AMD64 {
-                let local_tool = local_dir.join(sandbox_tool);
-                let link_info = LINK_OPTIONS
-                    .iter()
-                    .find(|l| l.id == sandbox_tool)
-                    .ok_or_else(|| format!("No download link found for tool: {sandbox_tool}"))?;
-
-                fs::create_dir_all(&local_dir)?;
-                download_file(link_info.link, local_dir, sandbox_tool)?;
-
-                let mut perms = fs::metadata(&local_tool)?.permissions();
-                perms.set_mode(0o755);
-                fs::set_permissions(&local_tool, perms)?;
-
-                local_tool
-            } else {
-                return Err(
-                    format!("{sandbox_tool} not found and no binary available for {arch}").into(),
-                );
-            }
-        }
-    };
+    let tool_target = resolve_tool(sandbox_tool)?;
+
+    let installed = fs::metadata(&tool_target)
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0);
+
+    if !installed {
+        let link = download_link(sandbox_tool, &app_arch())
+            .ok_or_else(|| format!("No download link found for tool: {sandbox_tool}"))?;
+
+        let options = DownloadOptions {
+            expected_sha256: Some(link.pinned_sha256(sandbox_tool)?),
+            ..Default::default()
+        };
+        fs::create_dir_all(&local_dir)?;
+        download_file_with(&mirrored_url(link.url), local_dir, sandbox_tool, &options)?;
+
+        let mut perms = fs::metadata(&tool_target)?.permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&tool_target, perms)?;
+
+        check_executable(&tool_target)?;
+    }
 
-    let _ = TOOL.set(SandboxTool {
+    TOOL.set(SandboxTool {
         name: sandbox_tool.to_string(),
         target: tool_target,
     });
@@ -177,52 +409,546 @@ pub fn set_sandbox_tool(sandbox_tool: &str) -> Result<(), Box<dyn std::error::Er
     Ok(())
 }
 
-/// Returns the application name from global config.
+/// Selects a sandbox tool binary at a given path, skipping the `PATH` lookup
+/// and the download done by [`set_sandbox_tool`].
+///
+/// Useful for reproducible environments that vendor their own build, such as
+/// a distro-patched PRoot. Like [`set_sandbox_tool`], only the first selection
+/// in a process takes effect.
+///
+/// # Arguments
+/// * `sandbox_tool` - The name of the tool (`proot` or `bwrap`).
+/// * `path` - The binary to run for it.
+///
+/// # Returns
+/// * `Ok(())` if the tool is known and the file is an executable.
+/// * `Err` otherwise, naming the rejected path.
+pub fn set_sandbox_tool_path(
+    sandbox_tool: &str,
+    path: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !SUPPORTED_TOOLS.contains(&sandbox_tool) {
+        return Err(format!("Unsupported sandbox tool: {sandbox_tool}").into());
+    }
+
+    let metadata = fs::metadata(&path)
+        .map_err(|e| format!("Cannot use {} as {sandbox_tool}: {e}", path.display()))?;
+    if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 {
+        return Err(format!("{} is not an executable file", path.display()).into());
+    }
+
+    TOOL.set(SandboxTool {
+        name: sandbox_tool.to_string(),
+        target: path,
+    });
+    Ok(())
+}
+
+/// Returns the version reported by a sandbox tool's `--version`.
+///
+/// The binary is the one selected for that tool, or else the one
+/// [`resolve_tool`] finds. The first token of
+/// the output shaped like a version number (`5.4.0`, `v0.8.0-rc1`) is
+/// returned, without a leading `v`.
+///
+/// # Arguments
+/// * `sandbox_tool` - The name of the tool (`proot` or `bwrap`).
+///
+/// # Returns
+/// The version, or `None` if the tool is missing, fails to run or prints no
+/// recognizable version.
+pub fn tool_version(sandbox_tool: &str) -> Option<String> {
+    let target = selected_tool(sandbox_tool).or_else(|| resolve_tool(sandbox_tool).ok())?;
+    let output = Command::new(target)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+
+    let text = [output.stdout, output.stderr].concat();
+    String::from_utf8_lossy(&text)
+        .split_whitespace()
+        .map(|token| {
+            token
+                .trim_start_matches('v')
+                .trim_end_matches([',', ';', ')'])
+        })
+        .find(|token| {
+            let mut parts = token.split('.');
+            let major = parts.next().unwrap_or_default();
+            let minor = parts.next().unwrap_or_default();
+            !major.is_empty()
+                && major.bytes().all(|b| b.is_ascii_digit())
+                && minor.bytes().next().is_some_and(|b| b.is_ascii_digit())
+        })
+        .map(str::to_string)
+}
+
+/// Re-downloads a sandbox tool installed by [`set_sandbox_tool`] when its
+/// published binary changed.
+///
+/// The download goes through `<cache>/tools`, which keeps the HTTP validators
+/// of the last copy, so an unchanged release is answered with `304 Not
+/// Modified` instead of being transferred again. A new binary is checked
+/// against its pinned SHA-256 and atomically renamed over the installed one.
+/// Tools found elsewhere in `PATH` (for instance from the distribution) are
+/// not touched.
+///
+/// # Arguments
+/// * `sandbox_tool` - The name of the tool (`proot` or `bwrap`).
+/// * `force` - Downloads and reinstalls the binary even if it is current.
+///
+/// # Returns
+/// * `Ok(true)` - If a new binary was installed.
+/// * `Ok(false)` - If the installed binary is already the published one.
+/// * `Err` - If the tool is not managed by this library, has no binary for
+///   this arch, its SHA-256 is not pinned, or the download or installation
+///   fails.
+pub fn update_tool(sandbox_tool: &str, force: bool) -> Result<bool, Box<dyn std::error::Error>> {
+    let local_dir = tool_install_dir();
+    let target = local_dir.join(sandbox_tool);
+
+    if let Some(found) = selected_tool(sandbox_tool).or_else(|| resolve_tool(sandbox_tool).ok())
+        && found != target
+    {
+        return Err(format!(
+            "{sandbox_tool} at {} is not managed by this library; update it with the system \
+             package manager",
+            found.display()
+        )
+        .into());
+    }
+
+    let arch = app_arch();
+    let link = download_link(sandbox_tool, &arch)
+        .ok_or_else(|| format!("No {sandbox_tool} binary available for {arch}"))?;
+
+    let cache_dir = default_cache().join("tools");
+    if force {
+        let _ = fs::remove_file(cache_dir.join(sandbox_tool));
+        let _ = fs::remove_file(cache_dir.join(format!("{sandbox_tool}.meta")));
+    }
+
+    let options = DownloadOptions {
+        revalidate: true,
+        hash: true,
+        expected_sha256: Some(link.pinned_sha256(sandbox_tool)?),
+        ..Default::default()
+    };
+    let result = download_file_with(&mirrored_url(link.url), cache_dir, sandbox_tool, &options)?;
+
+    if !force && target.exists() {
+        if result.cache_hit {
+            return Ok(false);
+        }
+        let mut hasher = Sha256::new();
+        io::copy(&mut fs::File::open(&target)?, &mut hasher)?;
+        if result.sha256 == Some(format!("{:x}", hasher.finalize())) {
+            return Ok(false);
+        }
+    }
+
+    // A running binary cannot be rewritten in place, but it can be replaced.
+    fs::create_dir_all(&local_dir)?;
+    let tmp = local_dir.join(format!(".{sandbox_tool}.{}.tmp", std::process::id()));
+    fs::copy(&result.path, &tmp)?;
+    fs::set_permissions(&tmp, fs::Permissions::from_mode(0o755))?;
+    fs::rename(&tmp, &target).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })?;
+    check_executable(&target)?;
+
+    Ok(true)
+}
+
+/// Returns the binary selected for a tool, if that tool is the selected one.
+fn selected_tool(sandbox_tool: &str) -> Option<PathBuf> {
+    TOOL.get()
+        .filter(|t| t.name == sandbox_tool)
+        .map(|t| t.target)
+}
+
+/// Resolves the path a sandbox tool would be used from, without side effects.
+///
+/// Searches `PATH` followed by [`tool_install_dir`], the same locations used
+/// by [`set_sandbox_tool`]. If the tool is not installed but can be downloaded
+/// for the current architecture, the path it would be installed to is
+/// returned instead. Neither the global tool nor the `PATH` variable is
+/// modified, so a UI can call this to show availability before committing.
+///
+/// # Arguments
+/// * `sandbox_tool` - The name of the tool to look up (`proot` or `bwrap`).
+///
+/// # Returns
+/// * `Ok(PathBuf)` with the existing binary or its future install location.
+/// * `Err` if the tool is missing and no binary is available for the current arch,
+///   or that binary has no pinned SHA-256.
+pub fn resolve_tool(sandbox_tool: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let arch = app_arch();
+    let local_dir = tool_install_dir();
+    let path = env::var_os("PATH").unwrap_or_default();
+    let search_path = format!("{}:{}", path.display(), local_dir.display());
+    let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
+
+    if let Ok(target) = which_in(sandbox_tool, Some(search_path), cwd) {
+        return Ok(target);
+    }
+
+    if let Some(link) = download_link(sandbox_tool, &arch) {
+        link.pinned_sha256(sandbox_tool)?;
+        return Ok(local_dir.join(sandbox_tool));
+    }
+
+    Err(format!("{sandbox_tool} not found and no binary available for {arch}").into())
+}
+
+/// Returns the published binary of a sandbox tool for an architecture.
+///
+/// # Arguments
+/// * `sandbox_tool` - The name of the tool (`proot` or `bwrap`).
+/// * `arch` - The architecture name, as reported by [`app_arch`].
+///
+/// # Returns
+/// The download target, or `None` if no binary is published for that tool and arch.
+fn download_link(sandbox_tool: &str, arch: &str) -> Option<&'static Target> {
+    LINK_OPTIONS
+        .iter()
+        .find(|l| l.id == sandbox_tool)?
+        .targets
+        .iter()
+        .find(|t| t.arch == arch)
+}
+
+/// Rewrites a built-in download URL to the mirror in [`TOOL_MIRROR_ENV`].
+///
+/// # Arguments
+/// * `url` - The built-in URL.
+///
+/// # Returns
+/// The mirror base followed by the URL path, or `url` itself when the
+/// variable is unset or empty.
+fn mirrored_url(url: &str) -> String {
+    let Some(mirror) = env::var(TOOL_MIRROR_ENV).ok().filter(|m| !m.is_empty()) else {
+        return url.to_string();
+    };
+
+    let after_scheme = url.find("://").map_or(0, |i| i + 3);
+    let path = url[after_scheme..]
+        .find('/')
+        .map_or("", |i| &url[after_scheme + i..]);
+    format!("{}{path}", mirror.trim_end_matches('/'))
+}
+
+/// Returns the names of the sandbox tools this crate can run, e.g. to fill a
+/// tool selector without hardcoding [`USE_PROOT`] and [`USE_BWRAP`].
+pub fn supported_tools() -> &'static [&'static str] {
+    &SUPPORTED_TOOLS
+}
+
+/// Checks whether a static binary of a sandbox tool is published, with a
+/// pinned SHA-256, for an architecture, so [`set_sandbox_tool`] can download
+/// it when it is missing.
+///
+/// Unlike [`can_use`], it needs no [`sandbox_init`] and looks at nothing on the
+/// host.
+///
+/// # Arguments
+/// * `sandbox_tool` - The name of the tool (`proot` or `bwrap`).
+/// * `arch` - Architecture name, as reported by [`app_arch`].
+///
+/// # Returns
+/// `true` if a download link with a built-in or [`set_tool_sha256`] digest
+/// exists; `false` for an unknown tool or arch, or an unpinned binary.
+pub fn tool_has_download(sandbox_tool: &str, arch: &str) -> bool {
+    download_link(sandbox_tool, arch).is_some_and(|t| t.pinned_sha256(sandbox_tool).is_ok())
+}
+
+/// Reports whether a sandbox tool would work on this host, without side effects.
+///
+/// Combines [`resolve_tool`] with, for Bubblewrap, a check of the kernel and
+/// AppArmor user namespace restrictions, so a UI can gray out an unusable
+/// backend before calling [`set_sandbox_tool`]. Requires [`sandbox_init`].
+///
+/// # Arguments
+/// * `sandbox_tool` - The name of the tool to query (`proot` or `bwrap`).
+///
+/// # Returns
+/// A [`ToolAvailability`] describing the tool.
+pub fn can_use(sandbox_tool: &str) -> ToolAvailability {
+    let installed = resolve_tool(sandbox_tool).ok().filter(|target| {
+        fs::metadata(target).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    });
+    let downloadable = tool_has_download(sandbox_tool, &app_arch());
+
+    let user_namespaces = (sandbox_tool == USE_BWRAP).then(|| {
+        let setuid = installed
+            .as_ref()
+            .and_then(|t| fs::metadata(t).ok())
+            .is_some_and(|m| m.permissions().mode() & 0o4000 != 0);
+        setuid || user_namespaces_allowed(installed.as_deref())
+    });
+
+    ToolAvailability {
+        tool: sandbox_tool.to_string(),
+        installed,
+        downloadable,
+        user_namespaces,
+    }
+}
+
+/// Checks whether an unprivileged process may create user namespaces.
+///
+/// Looks at the Debian `unprivileged_userns_clone` switch, the namespace
+/// limit, and the Ubuntu AppArmor restriction. Under the latter, a binary is
+/// only allowed if an AppArmor profile in `/etc/apparmor.d` names its path.
+///
+/// # Arguments
+/// * `binary` - The installed tool, checked against the AppArmor profiles.
+///
+/// # Returns
+/// `false` if any of the restrictions applies.
+fn user_namespaces_allowed(binary: Option<&Path>) -> bool {
+    let sysctl = |name: &str| {
+        fs::read_to_string(Path::new("/proc/sys").join(name))
+            .map(|v| v.trim().to_string())
+            .ok()
+    };
+
+    if sysctl("kernel/unprivileged_userns_clone").as_deref() == Some("0")
+        || sysctl("user/max_user_namespaces").as_deref() == Some("0")
+    {
+        return false;
+    }
+
+    if sysctl("kernel/apparmor_restrict_unprivileged_userns").as_deref() != Some("1") {
+        return true;
+    }
+
+    let Some(binary) = binary.map(|b| b.to_string_lossy().into_owned()) else {
+        return false;
+    };
+    fs::read_dir("/etc/apparmor.d")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .any(|profile| profile.contains(&binary))
+}
+
+/// Sets the directory where missing sandbox tools are downloaded to.
+///
+/// Must be called before [`set_sandbox_tool`]. When never called, tools are
+/// installed into `~/.local/bin`.
+///
+/// # Arguments
+/// * `path` - Directory used both for installation and PATH augmentation.
+pub fn set_tool_install_dir(path: PathBuf) {
+    INSTALL_DIR.set(path);
+}
+
+/// Pins the SHA-256 that a downloaded sandbox tool binary must match,
+/// replacing the built-in digest.
+///
+/// Needed when [`TOOL_MIRROR_ENV`] points to a mirror serving its own builds,
+/// or when the built-in binary for this architecture has no digest yet. Must
+/// be called before [`set_sandbox_tool`] or [`update_tool`]; a later call
+/// replaces the digest.
+///
+/// # Arguments
+/// * `sandbox_tool` - The name of the tool (`proot` or `bwrap`).
+/// * `sha256` - The digest, as 64 hex digits in either case.
+///
+/// # Returns
+/// * `Ok(())` - If the digest was stored.
+/// * `Err` - If `sha256` is not a SHA-256 hex digest.
+pub fn set_tool_sha256(sandbox_tool: &str, sha256: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("Invalid SHA-256 for {sandbox_tool}: {sha256:?}").into());
+    }
+    TOOL_SHA256
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(sandbox_tool.to_string(), sha256.to_ascii_lowercase());
+    Ok(())
+}
+
+/// Returns the digest given to [`set_tool_sha256`] for a tool, if any.
+fn pinned_override(sandbox_tool: &str) -> Option<String> {
+    TOOL_SHA256
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(sandbox_tool)
+        .cloned()
+}
+
+/// Returns the directory where missing sandbox tools are installed.
+pub fn tool_install_dir() -> PathBuf {
+    INSTALL_DIR
+        .get()
+        .unwrap_or_else(|| safe_home().join(".local").join("bin"))
+}
+
+/// Verifies that a freshly installed tool binary can actually be executed.
+///
+/// A `chmod 755` is not enough when the install directory lives on a
+/// filesystem mounted with `noexec`; the kernel then refuses to run the file
+/// and the failure would only surface later, inside `SandBox::run`.
+///
+/// # Arguments
+/// * `tool` - Path to the installed tool binary.
+///
+/// # Returns
+/// * `Ok(())` if the binary could be spawned.
+/// * `Err` with a hint about the `noexec` mount if execution was denied.
+fn check_executable(tool: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    match Command::new(tool)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+    {
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+            let dir = tool.parent().unwrap_or(tool);
+            Err(format!(
+                "{} was downloaded but cannot be executed: {e}\n\
+                 The directory {} is probably on a filesystem mounted with 'noexec'.\n\
+                 Choose another location with set_tool_install_dir() or remount it with exec permissions.",
+                tool.display(),
+                dir.display()
+            )
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Sets a canonical application name shown by the argument macros and dialogs.
+///
+/// By default the name is taken from the invoked binary (`argv[0]`), which
+/// varies when the program runs through a symlink or a wrapper. Can be called
+/// before or after [`sandbox_init`]; only the first call takes effect.
+///
+/// # Arguments
+/// * `name` - The display name to use instead of the detected one.
+pub fn set_app_name(name: &str) {
+    APP_NAME.set(name.to_string());
+}
+
+/// Returns the application name set by [`set_app_name`], or the one detected
+/// from `argv[0]` during [`sandbox_init`].
 pub fn app_name() -> String {
-    CONFIG.wait().app_name.clone()
+    APP_NAME
+        .get()
+        .unwrap_or_else(|| CONFIG.with(|c| c.app_name.clone()))
 }
 
 /// Returns the detected architecture from global config.
 pub fn app_arch() -> String {
-    CONFIG.wait().app_arch.clone()
+    CONFIG.with(|c| c.app_arch.clone())
 }
 
 /// Returns the safe home path from global config.
 pub fn safe_home() -> PathBuf {
-    CONFIG.wait().safe_home.clone()
+    CONFIG.with(|c| c.safe_home.clone())
 }
 
 /// Returns the configuration directory path.
 pub fn config_dir() -> PathBuf {
-    CONFIG.wait().config_dir.clone()
+    CONFIG.with(|c| c.config_dir.clone())
 }
 
 /// Returns the path to the configuration file.
 pub fn config_file() -> PathBuf {
-    CONFIG.wait().config_file.clone()
+    CONFIG.with(|c| c.config_file.clone())
 }
 
 /// Returns the default cache directory.
 pub fn default_cache() -> PathBuf {
-    CONFIG.wait().default_cache.clone()
+    CONFIG.with(|c| c.default_cache.clone())
 }
 
 /// Returns the default rootfs directory.
 pub fn default_rootfs() -> PathBuf {
-    CONFIG.wait().default_rootfs.clone()
+    CONFIG.with(|c| c.default_rootfs.clone())
+}
+
+/// Enables or disables per-user rootfs resolution.
+///
+/// When enabled, [`resolved_rootfs`] appends the real user ID to the default
+/// rootfs directory (`~/.<name>-<uid>`), so different identities sharing the
+/// same home never operate on the same rootfs. Disabled by default.
+///
+/// # Arguments
+/// * `enabled` - Whether to derive the rootfs path from the user ID.
+pub fn set_per_user_rootfs(enabled: bool) {
+    PER_USER_ROOTFS.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns the rootfs directory used by default for this process.
+///
+/// This is [`default_rootfs`], suffixed with `-<uid>` when per-user
+/// resolution is enabled through [`set_per_user_rootfs`].
+pub fn resolved_rootfs() -> PathBuf {
+    let rootfs = default_rootfs();
+
+    if !PER_USER_ROOTFS.load(Ordering::Relaxed) {
+        return rootfs;
+    }
+
+    let mut name = rootfs.file_name().unwrap_or_default().to_os_string();
+    name.push(format!("-{}", host_uid()));
+    rootfs.with_file_name(name)
+}
+
+/// Returns the real user ID of the current process.
+pub fn host_uid() -> u32 {
+    unsafe { getuid() }
+}
+
+/// Returns the effective user ID of the current process.
+pub fn host_euid() -> u32 {
+    unsafe { geteuid() }
 }
 
 /// Returns the temporary cache directory.
 pub fn temp_cache() -> PathBuf {
-    CONFIG.wait().temp_cache.clone()
+    CONFIG.with(|c| c.temp_cache.clone())
 }
 
 /// Returns the name of the selected sandbox tool.
 pub fn sandbox_tool() -> String {
-    TOOL.wait().name.clone()
+    TOOL.with(|t| t.name.clone())
+}
+
+/// Clears the state set by [`sandbox_init`], [`set_app_name`],
+/// [`set_tool_install_dir`], [`set_tool_sha256`] and [`set_per_user_rootfs`],
+/// so the next calls
+/// take effect again instead of being ignored.
+///
+/// Meant for tests that need a different app name, arch or paths within one
+/// process. Calls already waiting for the configuration keep waiting for the
+/// next [`sandbox_init`]. Requires the `test-util` feature.
+#[cfg(feature = "test-util")]
+pub fn reset_config() {
+    CONFIG.clear();
+    APP_NAME.clear();
+    INSTALL_DIR.clear();
+    TOOL_SHA256
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clear();
+    PER_USER_ROOTFS.store(false, Ordering::Relaxed);
+}
+
+/// Clears the tool selected by [`set_sandbox_tool`], so another one can be
+/// chosen. Requires the `test-util` feature.
+#[cfg(feature = "test-util")]
+pub fn reset_tool() {
+    TOOL.clear();
 }
 
 /// Returns the absolute path to the sandbox tool binary.
 pub fn tool_target() -> PathBuf {
-    TOOL.wait().target.clone()
+    TOOL.with(|t| t.target.clone())
 }
@@ -4,33 +4,79 @@
 //! using tools like `PRoot` and `Bubblewrap`. It handles everything from
 //! initialization and configuration to file downloading and sandboxed execution.
 
+mod bind;
+mod cgroup;
+mod config;
+mod diagnose;
 mod dialogs;
 mod init;
 mod macros;
 mod progress;
 mod sandbox;
+mod setup;
+mod shell;
+
+pub mod prelude;
+
+/// Re-exporting structured bind mounts and their builder.
+pub use bind::{BindMount, BindSet};
+
+/// Re-exporting versioning and migration helpers for `config.toml`.
+pub use config::{
+    migrate_config, migrate_config_file, UnsupportedConfigVersionError, CONFIG_VERSION,
+};
+
+/// Re-exporting the guest shell diagnosis used after failed launches.
+pub use diagnose::diagnose_shell;
 
 /// Re-exporting UI and formatting utilities for tables and dialogs.
 pub use dialogs::{
-    failed_exist_rootfs, get_cmd_box, get_config_diff, render_table, success_finish_setup,
-    SEPARATOR,
+    failed_exist_rootfs, get_cmd_box, get_config_changes, get_config_diff,
+    get_config_diff_ignoring, get_help_text, render_table, render_table_with, success_finish_setup,
+    Alignment, SEPARATOR,
 };
 
 /// Re-exporting overlay options.
 pub use overlayfs_fuse::{InodeMode, OverlayAction};
 
 /// Re-exporting core sandbox execution logic and configuration structures.
-pub use sandbox::{RootfsNotFoundError, SandBox, SandBoxConfig};
+pub use sandbox::{
+    validate_rootfs, ExtraBindReport, InvalidRootfsError, OverlayConfig, PseudoFs,
+    RootfsNotFoundError, SandBox, SandBoxConfig, ShellNotFoundError, TimedOutError,
+    UnsupportedOptionError, REQUIRED_ROOTFS_PATHS,
+};
+
+/// Re-exporting the one-call rootfs bootstrap.
+pub use setup::ensure_rootfs;
+
+/// Re-exporting POSIX shell quoting helpers for building guest commands.
+pub use shell::{shell_escape, shell_join};
 
 /// Re-exporting utilities for file transfer and bootstrap extraction.
-pub use progress::{download_file, extract_bootstrap};
+pub use progress::{
+    archive_roots, download_buffer_size, download_file, download_file_with,
+    download_file_with_progress, extract_bootstrap, extract_bootstrap_with,
+    extract_bootstrap_with_progress, extract_buffer_size, set_download_buffer_size,
+    set_extract_buffer_size, ArchiveFormat, CancelledError, ChecksumMismatchError,
+    DestinationNotEmptyError, DownloadCallback, DownloadOptions, DownloadProgress, DownloadResult,
+    ExtractCallback, ExtractOptions, ExtractProgress, ExtractionSummary, PathTraversalError,
+    ProgressOptions, SizeLimitExceededError, DEFAULT_DOWNLOAD_ATTEMPTS,
+    DEFAULT_DOWNLOAD_BUFFER_SIZE, DEFAULT_EXTRACT_BUFFER_SIZE, LARGE_ARCHIVE_SIZE,
+};
 
 /// Re-exporting initialization functions and environment getters.
 ///
 /// These functions manage the global state of the application paths and
 /// detect the host architecture.
 pub use init::{
-    app_arch, app_name, config_dir, config_file, default_cache, default_rootfs, safe_home,
-    sandbox_init, sandbox_tool, set_sandbox_tool, temp_cache, tool_target, USE_BWRAP,
-    USE_PROOT,
+    app_arch, app_name, can_use, config_dir, config_file, default_cache, default_rootfs, host_euid,
+    host_uid, resolve_tool, resolved_rootfs, safe_home, sandbox_init, sandbox_init_with,
+    sandbox_tool, set_app_name, set_per_user_rootfs, set_sandbox_tool, set_sandbox_tool_path,
+    set_tool_install_dir, set_tool_sha256, supported_tools, temp_cache, tool_has_download,
+    tool_install_dir, tool_target, tool_version, update_tool, HomeNotSetError, SandboxConfig,
+    ToolAvailability, TOOL_MIRROR_ENV, USE_BWRAP, USE_PROOT,
 };
+
+/// Re-exporting the global state reset helpers for tests.
+#[cfg(feature = "test-util")]
+pub use init::{reset_config, reset_tool};
@@ -110,3 +110,251 @@ macro_rules! parse_value {
         $crate::parse_value!($sub, $val_name, $arg, Option::<&str>::None)
     };
 }
+
+/// Parses a value (like [`parse_value!`]) and validates it against a fixed set.
+///
+/// Useful for flags such as `--tool=proot|bwrap` or `--format=gz|xz|zst`.
+///
+/// # Returns
+/// - `Ok(String)`: The extracted value, guaranteed to be one of `allowed`.
+/// - `Err(String)`: The `parse_value!` usage message if the value is missing,
+///   or a message listing the valid options if it is not allowed.
+#[macro_export]
+macro_rules! parse_enum {
+    ($sub:expr, $val_name:expr, $arg:expr, $next:expr, $allowed:expr) => {{
+        let allowed: &[&str] = $allowed;
+
+        $crate::parse_value!($sub, $val_name, $arg, $next).and_then(|value| {
+            if allowed.contains(&value.as_str()) {
+                Ok(value)
+            } else {
+                Err(format!(
+                    "{c}: {s}: invalid <{v}> '{value}'\nValid options: {o}\nUse '{c} --help' to see available options.",
+                    c = $crate::app_name(),
+                    s = $sub,
+                    v = $val_name,
+                    o = allowed.join(", ")
+                ))
+            }
+        })
+    }};
+
+    ($sub:expr, $val_name:expr, $arg:expr, $allowed:expr) => {
+        $crate::parse_enum!($sub, $val_name, $arg, Option::<&str>::None, $allowed)
+    };
+}
+
+/// Parses a value (like [`parse_value!`]) as an integer and checks it against a range.
+///
+/// The integer type is inferred from the range, e.g. `1u32..=10` yields a `u32`.
+///
+/// # Returns
+/// - `Ok(T)`: The parsed number, guaranteed to be inside `range`.
+/// - `Err(String)`: The `parse_value!` usage message if the value is missing,
+///   a "not a valid number" message if parsing fails, or a message naming
+///   the allowed bounds if the number is out of range.
+#[macro_export]
+macro_rules! parse_range {
+    ($sub:expr, $val_name:expr, $arg:expr, $next:expr, $range:expr) => {{
+        let range = $range;
+
+        $crate::parse_value!($sub, $val_name, $arg, $next).and_then(|value| {
+            let c = $crate::app_name();
+            match value.parse() {
+                Ok(number) if range.contains(&number) => Ok(number),
+                Ok(_) => Err(format!(
+                    "{c}: {}: <{}> '{value}' is out of range, expected a value within {:?}\nUse '{c} --help' to see available options.",
+                    $sub, $val_name, range
+                )),
+                Err(_) => Err(format!(
+                    "{c}: {}: <{}> '{value}' is not a valid number\nUse '{c} --help' to see available options.",
+                    $sub, $val_name
+                )),
+            }
+        })
+    }};
+
+    ($sub:expr, $val_name:expr, $arg:expr, $range:expr) => {
+        $crate::parse_range!($sub, $val_name, $arg, Option::<&str>::None, $range)
+    };
+}
+
+/// Validates that at most one of several mutually exclusive options is set.
+///
+/// Each option is given as `"name" => condition`, where `condition` is a `bool`
+/// telling whether the option was used.
+///
+/// # Returns
+/// - `Ok(())`: When zero or one option is set.
+/// - `Err`: An error naming every conflicting option, in the same style as
+///   [`invalid_arg!`].
+#[macro_export]
+macro_rules! mutually_exclusive {
+    ($sub:expr, $($name:expr => $set:expr),+ $(,)?) => {{
+        let conflicts: Vec<String> = [$(($name, $set)),+]
+            .iter()
+            .filter(|(_, set)| *set)
+            .map(|(name, _)| format!("'{name}'"))
+            .collect();
+
+        if conflicts.len() > 1 {
+            let c = $crate::app_name();
+            let context = if $sub.is_empty() {
+                c.to_string()
+            } else {
+                format!("{c}: {}", $sub)
+            };
+            let (last, rest) = conflicts.split_last().unwrap();
+
+            Err(format!(
+                "{}: options {} and {} cannot be used together\nUse '{c} --help' to see available options.",
+                context,
+                rest.join(", "),
+                last
+            )
+            .into())
+        } else {
+            Ok(())
+        }
+    }};
+}
+
+/// Collects the positional arguments left over after flag parsing.
+///
+/// Any argument starting with `-` is treated as a flag and skipped, except a
+/// lone `-` (commonly used for stdin). Flags listed in the optional second
+/// parameter take a value in `--key value` form, so the argument following
+/// them is skipped as well (`--key=value` needs no special handling).
+/// Everything after a literal `--` is collected verbatim, even if it starts
+/// with `-`.
+///
+/// # Returns
+/// - `Vec<String>`: The positional arguments, in their original order.
+#[macro_export]
+macro_rules! collect_positional {
+    ($args:expr, $value_flags:expr) => {{
+        let value_flags: &[&str] = $value_flags;
+        let mut positional: Vec<String> = Vec::new();
+        let mut iter = $args.into_iter();
+
+        while let Some(arg) = iter.next() {
+            let arg_ref: &str = arg.as_ref();
+
+            if arg_ref == "--" {
+                positional.extend(iter.map(|a| {
+                    let a_ref: &str = a.as_ref();
+                    a_ref.to_string()
+                }));
+                break;
+            }
+
+            if arg_ref.starts_with('-') && arg_ref != "-" {
+                if value_flags.contains(&arg_ref) {
+                    iter.next();
+                }
+                continue;
+            }
+
+            positional.push(arg_ref.to_string());
+        }
+
+        positional
+    }};
+
+    ($args:expr) => {
+        $crate::collect_positional!($args, &[])
+    };
+}
+
+/// Collects every occurrence of a repeated `KEY=VALUE` flag into a `HashMap`.
+///
+/// Both `--env=FOO=bar` and `--env FOO=bar` forms are accepted, using the same
+/// extraction rules as [`parse_value!`]. When a key appears more than once,
+/// the last occurrence wins.
+///
+/// # Returns
+/// - `Ok(HashMap<String, String>)`: The collected pairs (empty if the flag is absent).
+/// - `Err(String)`: The `parse_value!` usage message if a value is missing, or a
+///   message explaining the expected `KEY=VALUE` form if a value lacks `=` or has
+///   an empty key.
+#[macro_export]
+macro_rules! collect_key_values {
+    ($sub:expr, $val_name:expr, $args:expr, $flag:expr) => {{
+        let flag: &str = $flag;
+        let args: Vec<String> = $args
+            .into_iter()
+            .map(|a| {
+                let a_ref: &str = a.as_ref();
+                a_ref.to_string()
+            })
+            .collect();
+
+        (|| -> Result<std::collections::HashMap<String, String>, String> {
+            let mut map = std::collections::HashMap::new();
+
+            for (i, arg) in args.iter().enumerate() {
+                let is_flag = arg == flag
+                    || arg.strip_prefix(flag).is_some_and(|rest| rest.starts_with('='));
+                if !is_flag {
+                    continue;
+                }
+
+                let pair = $crate::parse_value!($sub, $val_name, arg, args.get(i + 1))?;
+                match pair.split_once('=') {
+                    Some((key, value)) if !key.is_empty() => {
+                        map.insert(key.to_string(), value.to_string());
+                    }
+                    _ => {
+                        return Err(format!(
+                            "{c}: {}: {flag} expects <{}> in KEY=VALUE form, got '{pair}'\nUse '{c} --help' to see available options.",
+                            $sub,
+                            $val_name,
+                            c = $crate::app_name()
+                        ));
+                    }
+                }
+            }
+
+            Ok(map)
+        })()
+    }};
+}
+
+/// Internal: emits an informational diagnostic.
+///
+/// Routed to `log::info!` when the `log` feature is enabled, discarded otherwise.
+macro_rules! log_info {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::info!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+/// Internal: emits a debug diagnostic, such as the final tool command line.
+///
+/// Routed to `log::debug!` when the `log` feature is enabled, discarded otherwise.
+macro_rules! log_debug {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+/// Internal: emits a warning.
+///
+/// Routed to `log::warn!` when the `log` feature is enabled. Otherwise it is
+/// printed to stderr with the yellow `Warning` prefix used across the crate.
+macro_rules! log_warn {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::warn!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        eprintln!("\x1b[1;33mWarning\x1b[0m: {}", format_args!($($arg)*));
+    }};
+}
+
+pub(crate) use {log_debug, log_info, log_warn};
@@ -0,0 +1,23 @@
+//! # Prelude
+//!
+//! A curated set of the most commonly used items, meant to be glob-imported:
+//!
+//! ```ignore
+//! use sandbox_utils::prelude::*;
+//! ```
+//!
+//! It covers the usual setup flow (initialization, tool selection, download,
+//! extraction, execution) and the dialog helpers, without pulling in every
+//! getter exported at the crate root.
+
+pub use crate::{
+    download_file, extract_bootstrap, failed_exist_rootfs, get_cmd_box, get_config_diff,
+    get_help_text, render_table, sandbox_init, set_sandbox_tool, shell_escape, shell_join,
+    success_finish_setup, validate_rootfs, BindSet, RootfsNotFoundError, SandBox, SandBoxConfig,
+    SEPARATOR, USE_BWRAP, USE_PROOT,
+};
+
+pub use crate::{
+    collect_key_values, collect_positional, invalid_arg, missing_arg, mutually_exclusive,
+    parse_enum, parse_range, parse_value,
+};
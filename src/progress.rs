@@ -3,23 +3,701 @@
 //! This module handles external resource acquisition and decompression.
 //! It provides visual feedback in the terminal using progress bars for both
 //! downloading files and extracting bootstrap archives.
+//!
+//! The bars are drawn with `indicatif` when the `progress` feature is enabled
+//! (default). Without it, the same operations run silently. Embedders such as
+//! GUIs or daemons can pass a callback instead, which replaces the bar.
 
-use indicatif::{ProgressBar, ProgressStyle};
+use crate::macros::{log_info, log_warn};
+#[cfg(feature = "progress")]
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
 use std::error::Error;
-use std::fs;
 use std::fs::File;
-use std::io::{self, BufWriter};
+use std::io::{self, BufWriter, Write};
 use std::io::{BufReader, Read};
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::result::Result;
-use tar::Archive;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+use std::{env, fmt, fs, thread};
+use tar::{Archive, EntryType};
+
+/// Leading part of the `indicatif` template shared by every bar style.
+const BASE_TEMPLATE: &str =
+    "{msg} {spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes}";
+
+/// Default capacity of the `BufWriter` used by [`download_file`] (std default, 8 KiB).
+pub const DEFAULT_DOWNLOAD_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Number of attempts [`download_file_with`] makes before giving up on a
+/// transient network error, when [`DownloadOptions::attempts`] is `None`.
+pub const DEFAULT_DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry of a failed download; doubled after each retry.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Default capacity of the `BufReader` used by [`extract_bootstrap`] (64 KiB).
+pub const DEFAULT_EXTRACT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Compressed size from which an archive counts as large: its decompression
+/// moves to a background thread and zstd accepts long-distance windows (256 MiB).
+pub const LARGE_ARCHIVE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// zstd window limit (log2) for regular archives, the library default (128 MiB).
+const ZSTD_WINDOW_LOG_DEFAULT: u32 = 27;
+
+/// zstd window limit (log2) for large archives, enough for `zstd --long=31` (2 GiB).
+const ZSTD_WINDOW_LOG_LARGE: u32 = 31;
+
+/// Size of each decompressed chunk handed over by the background decoder.
+const DECODE_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Number of chunks queued between the background decoder and the unpacker.
+const DECODE_QUEUE_LEN: usize = 8;
+
+/// Bytes read from the start of an archive to detect its format.
+const MAGIC_LEN: usize = 262;
+
+/// Current write buffer capacity for downloads.
+static DOWNLOAD_BUFFER_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_DOWNLOAD_BUFFER_SIZE);
+
+/// Current read buffer capacity for extractions.
+static EXTRACT_BUFFER_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_EXTRACT_BUFFER_SIZE);
+
+/// Sets the write buffer capacity used by [`download_file`].
+///
+/// Larger buffers reduce the number of `write(2)` calls on fast disks.
+/// A value of `0` is ignored.
+///
+/// # Arguments
+/// * `size` - Buffer capacity in bytes.
+pub fn set_download_buffer_size(size: usize) {
+    if size > 0 {
+        DOWNLOAD_BUFFER_SIZE.store(size, Ordering::Relaxed);
+    }
+}
+
+/// Sets the read buffer capacity used by [`extract_bootstrap`].
+///
+/// Larger buffers feed slow decompressors (xz) in bigger chunks and reduce
+/// syscall overhead for large rootfs archives. A value of `0` is ignored.
+///
+/// # Arguments
+/// * `size` - Buffer capacity in bytes.
+pub fn set_extract_buffer_size(size: usize) {
+    if size > 0 {
+        EXTRACT_BUFFER_SIZE.store(size, Ordering::Relaxed);
+    }
+}
+
+/// Returns the write buffer capacity used by [`download_file`].
+pub fn download_buffer_size() -> usize {
+    DOWNLOAD_BUFFER_SIZE.load(Ordering::Relaxed)
+}
+
+/// Returns the read buffer capacity used by [`extract_bootstrap`].
+pub fn extract_buffer_size() -> usize {
+    EXTRACT_BUFFER_SIZE.load(Ordering::Relaxed)
+}
+
+/// Compression formats understood by [`extract_bootstrap_with`].
+///
+/// Every variant exists regardless of the enabled crate features; selecting
+/// one whose feature is disabled fails at extraction time with a clear error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// gzip (`.tar.gz`, `.tgz`), requires the `gz` feature.
+    Gz,
+    /// xz/LZMA (`.tar.xz`, `.txz`), requires the `xz` feature.
+    Xz,
+    /// Zstandard (`.tar.zst`, `.tzst`), requires the `zst` feature.
+    Zst,
+    /// bzip2 (`.tar.bz2`, `.tbz2`, `.tbz`), requires the `bz2` feature.
+    Bz2,
+    /// Uncompressed tarball (`.tar`), always available.
+    Tar,
+}
+
+impl ArchiveFormat {
+    /// Maps a normalized file extension (`gz`, `xz`, `zst`, `zstd`, `bz2`, `tar`)
+    /// to a format.
+    ///
+    /// # Arguments
+    /// * `ext` - The extension, without the leading dot.
+    ///
+    /// # Returns
+    /// The matching format, or `None` if the extension is unknown.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "gz" => Some(Self::Gz),
+            "xz" => Some(Self::Xz),
+            "zst" | "zstd" => Some(Self::Zst),
+            "bz2" => Some(Self::Bz2),
+            "tar" => Some(Self::Tar),
+            _ => None,
+        }
+    }
+
+    /// Identifies a format from the first bytes of an archive.
+    ///
+    /// # Arguments
+    /// * `header` - The start of the file; 262 bytes are enough for every
+    ///   format, a plain tarball being recognized by the `ustar` marker at
+    ///   offset 257 of its first header.
+    ///
+    /// # Returns
+    /// The format whose magic number `header` starts with, or `None`.
+    pub fn from_magic(header: &[u8]) -> Option<Self> {
+        match header {
+            [0x1f, 0x8b, ..] => Some(Self::Gz),
+            [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(Self::Xz),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Self::Zst),
+            [b'B', b'Z', b'h', b'1'..=b'9', ..] => Some(Self::Bz2),
+            _ if header.get(257..262) == Some(b"ustar") => Some(Self::Tar),
+            _ => None,
+        }
+    }
+
+    /// Returns the canonical extension of the format, which is also the name
+    /// of the crate feature enabling it (`tar` needs no feature).
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Gz => "gz",
+            Self::Xz => "xz",
+            Self::Zst => "zst",
+            Self::Bz2 => "bz2",
+            Self::Tar => "tar",
+        }
+    }
+}
+
+/// Layout of the progress bar drawn by downloads and extractions.
+///
+/// The default reproduces the historical look: elapsed time, bar, byte
+/// counters and ETA. These options are ignored when the `progress` feature
+/// is disabled.
+#[derive(Clone, Debug)]
+pub struct ProgressOptions {
+    /// Shows the transfer rate (`{bytes_per_sec}`).
+    pub show_rate: bool,
+    /// Shows the estimated time remaining (`{eta}`).
+    pub show_eta: bool,
+    /// Shows the completion percentage (`{percent}%`).
+    pub show_percent: bool,
+    /// Complete `indicatif` template; when set, the flags above are ignored.
+    pub template: Option<String>,
+}
+
+impl Default for ProgressOptions {
+    fn default() -> Self {
+        Self {
+            show_rate: false,
+            show_eta: true,
+            show_percent: false,
+            template: None,
+        }
+    }
+}
+
+impl ProgressOptions {
+    /// Builds the `indicatif` template string described by these options.
+    ///
+    /// # Returns
+    /// The custom `template` if one was given, otherwise the base template
+    /// extended with the enabled percentage, rate and ETA fields.
+    pub fn to_template(&self) -> String {
+        if let Some(template) = &self.template {
+            return template.clone();
+        }
+
+        let mut template = String::from(BASE_TEMPLATE);
+        if self.show_percent {
+            template.push_str(" {percent}%");
+        }
+        if self.show_rate {
+            template.push_str(" {bytes_per_sec}");
+        }
+        if self.show_eta {
+            template.push_str(" ({eta})");
+        }
+        template
+    }
+}
+
+/// Snapshot of a running download, passed to a [`DownloadCallback`].
+pub struct DownloadProgress<'a> {
+    /// Bytes received so far.
+    pub downloaded: u64,
+    /// Expected size from `Content-Length`, or `0` if the server did not send it.
+    pub total: u64,
+    hasher: Option<&'a Sha256>,
+}
+
+impl DownloadProgress<'_> {
+    /// Returns the SHA-256 of the bytes received so far, as lowercase hex.
+    ///
+    /// The digest is only computed when this method is called, so callers that
+    /// display it can throttle the work. Returns `None` unless hashing was
+    /// requested with [`DownloadOptions::hash`].
+    pub fn sha256(&self) -> Option<String> {
+        self.hasher.map(|h| format!("{:x}", h.clone().finalize()))
+    }
+}
+
+/// Callback invoked after every chunk written by [`download_file_with`].
+#[derive(Clone)]
+pub struct DownloadCallback(Arc<dyn Fn(&DownloadProgress<'_>) + Send + Sync>);
+
+impl DownloadCallback {
+    /// Wraps a closure receiving each [`DownloadProgress`] update.
+    pub fn new(f: impl Fn(&DownloadProgress<'_>) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for DownloadCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DownloadCallback(..)")
+    }
+}
+
+/// Snapshot of a running extraction, passed to an [`ExtractCallback`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtractProgress {
+    /// Compressed bytes read from the archive so far.
+    pub read: u64,
+    /// Size of the archive file, in bytes.
+    pub total: u64,
+}
+
+/// Shareable closure invoked with an [`ExtractProgress`] as the archive is read.
+#[derive(Clone)]
+pub struct ExtractCallback(Arc<dyn Fn(&ExtractProgress) + Send + Sync>);
+
+impl ExtractCallback {
+    /// Wraps a closure receiving each [`ExtractProgress`] update.
+    pub fn new(f: impl Fn(&ExtractProgress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for ExtractCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ExtractCallback(..)")
+    }
+}
+
+/// Per-call options for [`download_file_with`].
+#[derive(Clone, Debug, Default)]
+pub struct DownloadOptions {
+    /// Progress bar layout.
+    pub progress: ProgressOptions,
+    /// Receives the byte count (and running digest) after every chunk. When
+    /// set, it replaces the terminal bar, which is not drawn.
+    pub on_progress: Option<DownloadCallback>,
+    /// If true, hashes the bytes with SHA-256 while they are written, exposing
+    /// the running digest through [`DownloadProgress::sha256`]. Off by default
+    /// to keep the plain copy path as fast as possible.
+    pub hash: bool,
+    /// Flag checked before every read; setting it to `true` aborts the
+    /// download, removes the partial file and returns [`CancelledError`].
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// If true, an existing file is revalidated instead of skipped. The
+    /// `ETag`/`Last-Modified` validators are kept in a `<filename>.meta`
+    /// sidecar and sent as a conditional GET: a `304` reuses the file and
+    /// anything else is downloaded again. Without it, the sidecar only lives
+    /// while a download is in progress.
+    pub revalidate: bool,
+    /// Attempts made before giving up when the connection fails, times out or
+    /// the server answers `5xx`; other errors, such as `404`, fail at once.
+    /// Retries wait 0.5 s, then twice as long each time. A partial file is
+    /// truncated before the next attempt, unless the server lets it be
+    /// resumed. When `None`, [`DEFAULT_DOWNLOAD_ATTEMPTS`]; `Some(1)`
+    /// disables retrying.
+    pub attempts: Option<u32>,
+    /// Expected SHA-256 of the file, as hex. The bytes are hashed while they
+    /// are written (an existing file is hashed when skipped), and on mismatch
+    /// the file is deleted and [`ChecksumMismatchError`] returned, so the next
+    /// call downloads it again.
+    pub expected_sha256: Option<String>,
+    /// Proxy for this download, e.g. `http://proxy.corp:3128` or
+    /// `socks5://127.0.0.1:1080`, used for every host. An empty string
+    /// disables proxying. When `None`, the proxy comes from the environment;
+    /// see [`download_file_with`].
+    pub proxy: Option<String>,
+}
+
+/// Per-call options for [`extract_bootstrap_with`].
+#[derive(Clone, Debug, Default)]
+pub struct ExtractOptions {
+    /// Progress bar layout.
+    pub progress: ProgressOptions,
+    /// Receives the compressed byte count after every chunk read from the
+    /// archive. When set, it replaces the terminal bar, which is not drawn.
+    pub on_progress: Option<ExtractCallback>,
+    /// Flag checked before every read; setting it to `true` aborts the
+    /// extraction, removes the partially unpacked rootfs and returns
+    /// [`CancelledError`].
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// If true, fails with [`DestinationNotEmptyError`] when the `rootfs`
+    /// directory already has entries, instead of merging the archive into it.
+    pub require_empty: bool,
+    /// Forces a decoder regardless of the file contents. When `None`, the
+    /// format is detected from the magic number at the start of the file,
+    /// falling back to the extension when it is not recognized.
+    pub format: Option<ArchiveFormat>,
+    /// Decompresses on a separate thread, so decoding overlaps with writing the
+    /// unpacked files. The hand-off queue holds at most 8 MiB. When `None`, it
+    /// is enabled for archives of [`LARGE_ARCHIVE_SIZE`] or more; small
+    /// archives stay single-threaded. zstd itself has no multithreaded
+    /// decoder, so one extra thread is the most that can be used.
+    pub background_decode: Option<bool>,
+    /// Largest zstd window accepted, as a power of two. A frame only allocates
+    /// the window it declares, so this caps memory use per decoder. When
+    /// `None`, it is 27 (128 MiB, the zstd default), raised to 31 (2 GiB) for
+    /// large archives so images made with `zstd --long=31` decode.
+    pub zstd_window_log_max: Option<u32>,
+    /// Number of leading path components dropped from every entry, like
+    /// `tar --strip-components`. Use `1` for tarballs that wrap the rootfs in
+    /// a single folder such as `archlinux-bootstrap/`; [`archive_roots`] tells
+    /// whether an archive does. Entries left with an empty path, such as the
+    /// wrapping folder itself, are skipped, and hard link targets are
+    /// stripped the same way.
+    pub strip_components: usize,
+    /// Upper bound on the unpacked size, in bytes, summed over the entry
+    /// sizes. The entry that would cross it is not written: the extraction
+    /// fails with [`SizeLimitExceededError`] and the partial rootfs is removed
+    /// if the call created it. `None` means no limit.
+    pub max_uncompressed: Option<u64>,
+}
+
+/// Outcome of a [`download_file_with`] call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DownloadResult {
+    /// Full path of the file on disk (`dest` joined with `filename`).
+    pub path: PathBuf,
+    /// Name of the saved file.
+    pub filename: String,
+    /// Final size of the file, in bytes.
+    pub size: u64,
+    /// True if the file already existed and no data was transferred.
+    pub skipped: bool,
+    /// True if the server confirmed the cached file is current (`304 Not Modified`).
+    pub cache_hit: bool,
+    /// True if a partial file was completed with a ranged request.
+    pub resumed: bool,
+    /// Wall-clock time spent in the call, including the skip check.
+    pub elapsed: Duration,
+    /// SHA-256 hex digest of the file, when [`DownloadOptions::hash`] or
+    /// [`DownloadOptions::expected_sha256`] is set.
+    pub sha256: Option<String>,
+}
+
+/// Totals gathered while unpacking an archive with [`extract_bootstrap_with`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExtractionSummary {
+    /// Number of archive entries unpacked (files, directories, links, ...).
+    pub entries: u64,
+    /// Sum of the uncompressed entry sizes, in bytes.
+    pub total_bytes: u64,
+    /// Wall-clock time spent in the call, from the first check to the last entry.
+    pub elapsed: Duration,
+}
+
+/// Error returned when a download or extraction is stopped through its
+/// `cancel` flag.
+#[derive(Debug)]
+pub struct CancelledError;
+
+impl fmt::Display for CancelledError {
+    /// Formats the error message for a cancelled operation.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Operation cancelled")
+    }
+}
+
+impl Error for CancelledError {}
+
+/// Custom error type for an extraction target that already has entries.
+///
+/// Holds the `rootfs` directory that was expected to be empty.
+#[derive(Debug)]
+pub struct DestinationNotEmptyError(pub PathBuf);
+
+impl fmt::Display for DestinationNotEmptyError {
+    /// Formats the error message for the non-empty destination.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Extraction destination is not empty: {:?}", self.0)
+    }
+}
+
+impl Error for DestinationNotEmptyError {}
+
+/// Custom error type for a downloaded file whose SHA-256 differs from the
+/// expected one. The file is deleted before this error is returned.
+#[derive(Debug)]
+pub struct ChecksumMismatchError {
+    /// Path of the rejected (and removed) file.
+    pub path: PathBuf,
+    /// Expected SHA-256, as lowercase hex.
+    pub expected: String,
+    /// SHA-256 of the received bytes, as lowercase hex.
+    pub actual: String,
+}
 
-/// Template string for the `indicatif` progress bar styling.
-const DOWNLOAD_TEMPLATE: &str = "{msg} {spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({eta})";
+impl fmt::Display for ChecksumMismatchError {
+    /// Formats the error message with both digests.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Checksum mismatch for {:?}: expected {}, got {}",
+            self.path, self.expected, self.actual
+        )
+    }
+}
+
+impl Error for ChecksumMismatchError {}
+
+/// Custom error type for an archive entry that would be written outside the
+/// extraction root, through `..` components, a symlink or a hard link.
+///
+/// Holds the offending entry. Nothing is written for it, and the partial
+/// extraction is removed if the call created the `rootfs` directory.
+#[derive(Debug)]
+pub struct PathTraversalError(pub PathBuf);
+
+impl fmt::Display for PathTraversalError {
+    /// Formats the error message for the escaping entry.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Archive entry escapes the extraction root: {:?}", self.0)
+    }
+}
+
+impl Error for PathTraversalError {}
+
+/// Custom error type for an archive that unpacks to more than
+/// [`ExtractOptions::max_uncompressed`] bytes.
+///
+/// Holds the limit that was exceeded.
+#[derive(Debug)]
+pub struct SizeLimitExceededError(pub u64);
+
+impl fmt::Display for SizeLimitExceededError {
+    /// Formats the error message with the exceeded limit.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Archive unpacks to more than {} bytes", self.0)
+    }
+}
+
+impl Error for SizeLimitExceededError {}
+
+/// Reader adapter that fails as soon as the cancel flag is raised.
+///
+/// The copy and unpack loops pull data through this reader, so the flag is
+/// observed at every chunk without changing the loops themselves.
+struct CancelReader<R> {
+    inner: R,
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+impl<R: Read> Read for CancelReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if is_cancelled(&self.cancel) {
+            return Err(io::Error::other(CancelledError));
+        }
+        self.inner.read(buf)
+    }
+}
+
+/// Reader that decompresses on a separate thread, overlapping decoding with
+/// the writes done by the unpacker.
+///
+/// Chunks go through a bounded queue; dropping the reader stops the thread at
+/// its next hand-off.
+struct BackgroundReader {
+    rx: mpsc::Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl BackgroundReader {
+    /// Starts the decoding thread for `inner`.
+    fn spawn(mut inner: Box<dyn Read + Send>) -> Self {
+        let (tx, rx) = mpsc::sync_channel(DECODE_QUEUE_LEN);
+
+        thread::spawn(move || loop {
+            let mut chunk = vec![0u8; DECODE_CHUNK_SIZE];
+            let result = match inner.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => {
+                    chunk.truncate(n);
+                    Ok(chunk)
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+            let failed = result.is_err();
+            if tx.send(result).is_err() || failed {
+                break;
+            }
+        });
+
+        Self {
+            rx,
+            chunk: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl Read for BackgroundReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.chunk.len() {
+            match self.rx.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.pos = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Reader adapter that counts, optionally hashes and reports downloaded bytes.
+struct TrackReader<R> {
+    inner: R,
+    downloaded: u64,
+    total: u64,
+    hasher: Option<Sha256>,
+    callback: Option<DownloadCallback>,
+}
+
+impl<R: Read> Read for TrackReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.downloaded += n as u64;
+
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&buf[..n]);
+        }
+
+        if let Some(callback) = &self.callback {
+            (callback.0)(&DownloadProgress {
+                downloaded: self.downloaded,
+                total: self.total,
+                hasher: self.hasher.as_ref(),
+            });
+        }
+
+        Ok(n)
+    }
+}
+
+/// Reader adapter that reports the compressed bytes read from an archive.
+struct ExtractTrackReader<R> {
+    inner: R,
+    read: u64,
+    total: u64,
+    callback: ExtractCallback,
+}
+
+impl<R: Read> Read for ExtractTrackReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        (self.callback.0)(&ExtractProgress {
+            read: self.read,
+            total: self.total,
+        });
+        Ok(n)
+    }
+}
+
+/// Returns `true` if the optional cancel flag is set.
+fn is_cancelled(cancel: &Option<Arc<AtomicBool>>) -> bool {
+    cancel.as_ref().is_some_and(|c| c.load(Ordering::Relaxed))
+}
+
+/// Terminal progress reporter shared by downloads and extractions.
+///
+/// Wraps an `indicatif::ProgressBar` when the `progress` feature is enabled
+/// and compiles down to a no-op otherwise, so callers never need `cfg` checks.
+struct Progress {
+    #[cfg(feature = "progress")]
+    bar: ProgressBar,
+}
+
+impl Progress {
+    /// Creates a reporter sized to `total` bytes, showing `message` while
+    /// active. A `hidden` reporter tracks progress without drawing anything.
+    #[cfg(feature = "progress")]
+    fn new(
+        total: u64,
+        message: &'static str,
+        options: &ProgressOptions,
+        hidden: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        let target = match hidden {
+            true => ProgressDrawTarget::hidden(),
+            false => ProgressDrawTarget::stderr(),
+        };
+        let bar = ProgressBar::with_draw_target(Some(total), target);
+        bar.set_message(message);
+        bar.set_style(ProgressStyle::with_template(&options.to_template())?.progress_chars("##-"));
+        Ok(Self { bar })
+    }
+
+    /// Creates a silent reporter when the `progress` feature is disabled.
+    #[cfg(not(feature = "progress"))]
+    fn new(
+        _total: u64,
+        _message: &'static str,
+        _options: &ProgressOptions,
+        _hidden: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {})
+    }
+
+    /// Wraps a reader so that every read advances the bar.
+    #[cfg(feature = "progress")]
+    fn wrap_read<R: Read + Send>(&self, reader: R) -> impl Read + Send + use<R> {
+        self.bar.wrap_read(reader)
+    }
+
+    /// Returns the reader untouched when the `progress` feature is disabled.
+    #[cfg(not(feature = "progress"))]
+    fn wrap_read<R: Read + Send>(&self, reader: R) -> impl Read + Send + use<R> {
+        reader
+    }
+
+    /// Completes the bar, replacing its message with `message`.
+    #[cfg(feature = "progress")]
+    fn finish(&self, message: &'static str) {
+        self.bar.finish_with_message(message);
+    }
+
+    /// Does nothing when the `progress` feature is disabled.
+    #[cfg(not(feature = "progress"))]
+    fn finish(&self, _message: &'static str) {}
+}
 
 /// Downloads a file from a URL to a local destination with a progress bar.
 ///
 /// If the file already exists at the destination, the download is skipped.
+/// A file left incomplete by an interrupted call, detected by comparing its
+/// size with the `Content-Length` recorded in its `<filename>.meta` sidecar,
+/// is resumed with an HTTP `Range` request instead; it is downloaded again from
+/// scratch when the server sent no `ETag`/`Last-Modified`, ignores the range,
+/// or rejects it with `416 Range Not Satisfiable`.
 ///
 /// # Arguments
 /// * `url` - The source URL of the file.
@@ -30,38 +708,499 @@ const DOWNLOAD_TEMPLATE: &str = "{msg} {spinner:.green} [{elapsed_precise}] [{wi
 /// * `Ok(())` - If the file was downloaded successfully or already exists.
 /// * `Err` - If networked, I/O, or directory creation fails.
 pub fn download_file(url: &str, dest: PathBuf, filename: &str) -> Result<(), Box<dyn Error>> {
+    download_file_with(url, dest, filename, &DownloadOptions::default())?;
+    Ok(())
+}
+
+/// Same as [`download_file`], reporting progress to a closure instead of
+/// drawing a terminal bar.
+///
+/// # Arguments
+/// * `url` - The source URL of the file.
+/// * `dest` - The directory where the file should be saved.
+/// * `filename` - The name to give to the downloaded file.
+/// * `on_progress` - Called with `(downloaded, total)` after every chunk;
+///   `total` is `0` when the server sends no `Content-Length`.
+///
+/// # Returns
+/// * `Ok(DownloadResult)` - As for [`download_file_with`].
+/// * `Err` - If networked, I/O, or directory creation fails.
+pub fn download_file_with_progress(
+    url: &str,
+    dest: PathBuf,
+    filename: &str,
+    on_progress: impl Fn(u64, u64) + Send + Sync + 'static,
+) -> Result<DownloadResult, Box<dyn Error>> {
+    let options = DownloadOptions {
+        on_progress: Some(DownloadCallback::new(move |p| {
+            on_progress(p.downloaded, p.total)
+        })),
+        ..Default::default()
+    };
+    download_file_with(url, dest, filename, &options)
+}
+
+/// Same as [`download_file`], with per-call [`DownloadOptions`].
+///
+/// Unless [`DownloadOptions::proxy`] is set, requests go through the proxy in
+/// `HTTPS_PROXY` for `https://` URLs or `HTTP_PROXY` for `http://` ones,
+/// falling back to `ALL_PROXY` (lowercase names are accepted too). Hosts
+/// listed in `NO_PROXY`, a comma-separated list, are reached directly:
+/// * `*` matches every host.
+/// * `example.com`, `.example.com` and `*.example.com` all match
+///   `example.com` and any of its subdomains, as in curl.
+/// * Matching is case-insensitive and ignores ports; IP ranges in CIDR
+///   notation are not supported, but a literal address matches itself.
+///
+/// # Arguments
+/// * `url` - The source URL of the file.
+/// * `dest` - The directory where the file should be saved.
+/// * `filename` - The name to give to the downloaded file.
+/// * `options` - Progress bar layout and other per-call settings.
+///
+/// # Returns
+/// * `Ok(DownloadResult)` - The saved file, its size, timing and whether the
+///   download was skipped, confirmed by the server cache or resumed.
+/// * `Err` - If networked, I/O, or directory creation fails.
+///   [`CancelledError`] if the `cancel` flag was raised.
+///   [`ChecksumMismatchError`] if the file differs from `expected_sha256`.
+pub fn download_file_with(
+    url: &str,
+    dest: PathBuf,
+    filename: &str,
+    options: &DownloadOptions,
+) -> Result<DownloadResult, Box<dyn Error>> {
+    let started = Instant::now();
+    let attempts = options.attempts.unwrap_or(DEFAULT_DOWNLOAD_ATTEMPTS).max(1);
+    let mut delay = RETRY_BASE_DELAY;
+    let mut attempt = 1;
+
+    let result = loop {
+        match download_once(url, &dest, filename, options, started) {
+            Err(e) if attempt < attempts && is_transient(e.as_ref()) => {
+                log_warn!(
+                    "Download of {url} failed ({e}), retrying in {:.1}s ({attempt}/{attempts})",
+                    delay.as_secs_f64()
+                );
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            result => break result?,
+        }
+    };
+
+    match (&options.expected_sha256, &result.sha256) {
+        (Some(expected), Some(actual)) if !actual.eq_ignore_ascii_case(expected) => {
+            let _ = fs::remove_file(&result.path);
+            let _ = fs::remove_file(dest.join(format!("{filename}.meta")));
+            Err(Box::new(ChecksumMismatchError {
+                path: result.path,
+                expected: expected.to_lowercase(),
+                actual: actual.clone(),
+            }))
+        }
+        _ => Ok(result),
+    }
+}
+
+/// Makes a single download attempt for [`download_file_with`].
+///
+/// # Arguments
+/// * `url` - The source URL of the file.
+/// * `dest` - The directory where the file should be saved.
+/// * `filename` - The name to give to the downloaded file.
+/// * `options` - The per-call settings.
+/// * `started` - When the download call began, for [`DownloadResult::elapsed`].
+///
+/// # Returns
+/// * `Ok(DownloadResult)` - The saved file.
+/// * `Err` - The first network or I/O error; a partial file is left on disk
+///   only when it can be resumed.
+fn download_once(
+    url: &str,
+    dest: &Path,
+    filename: &str,
+    options: &DownloadOptions,
+    started: Instant,
+) -> Result<DownloadResult, Box<dyn Error>> {
     let save_path = dest.join(filename);
+    let meta_path = dest.join(format!("{filename}.meta"));
+    let hash = options.hash || options.expected_sha256.is_some();
+    let on_disk = fs::metadata(&save_path).map(|m| m.len()).ok();
+    let cached = on_disk.and_then(|_| CacheMeta::read(&meta_path));
+    let partial = match (on_disk, cached.as_ref().and_then(|m| m.length)) {
+        (Some(len), Some(total)) => len < total,
+        _ => false,
+    };
 
-    if save_path.exists() {
-        return Ok(());
+    if on_disk.is_some() && !options.revalidate && !partial {
+        log_info!("{} already exists, skipping download", save_path.display());
+        return existing_result(save_path, filename, started, hash, false);
     }
 
-    fs::create_dir_all(&dest)?;
-    let resp = ureq::get(url).call()?;
+    log_info!("Downloading {url} to {}", save_path.display());
 
-    let total_size = resp
-        .headers()
-        .get("Content-Length")
-        .and_then(|v| v.to_str().unwrap().parse::<u64>().ok())
+    fs::create_dir_all(dest)?;
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .proxy(download_proxy(url, options.proxy.as_deref())?)
+        .build()
+        .into();
+    let mut request = agent.get(url);
+    let mut resume_from = None;
+
+    if let Some(meta) = &cached {
+        let len = on_disk.unwrap_or(0);
+        match (partial, meta.range_validator()) {
+            (true, Some(validator)) => {
+                request = request
+                    .header("Range", format!("bytes={len}-"))
+                    .header("If-Range", validator);
+                resume_from = Some(len);
+            }
+            (true, None) => {
+                log_info!(
+                    "{} is incomplete and cannot be resumed, downloading it again",
+                    save_path.display()
+                );
+            }
+            (false, _) if options.revalidate => {
+                if let Some(etag) = &meta.etag {
+                    request = request.header("If-None-Match", etag);
+                }
+                if let Some(modified) = &meta.last_modified {
+                    request = request.header("If-Modified-Since", modified);
+                }
+            }
+            (false, _) => {}
+        }
+    }
+
+    let resp = match request.call() {
+        // The partial file no longer fits the remote one (it shrank, or the
+        // sidecar is stale), so the range cannot be served: start over.
+        Err(ureq::Error::StatusCode(416)) if resume_from.is_some() => {
+            log_info!(
+                "Server refused to resume {}, downloading it again",
+                save_path.display()
+            );
+            fs::remove_file(&save_path)?;
+            let _ = fs::remove_file(&meta_path);
+            return download_once(url, dest, filename, options, started);
+        }
+        resp => resp?,
+    };
+    let status = resp.status().as_u16();
+
+    if status == 304 {
+        log_info!("{} is up to date, reusing it", save_path.display());
+        return existing_result(save_path, filename, started, hash, true);
+    }
+
+    let header = |name: &str| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let body_size = header("Content-Length")
+        .and_then(|v| v.parse::<u64>().ok())
         .unwrap_or(0);
+    let offset = resume_from.filter(|_| status == 206).unwrap_or(0);
+    let total_size = offset + body_size;
 
-    let pb = ProgressBar::new(total_size);
-    pb.set_message("Downloading...");
-    pb.set_style(ProgressStyle::with_template(DOWNLOAD_TEMPLATE)?.progress_chars("##-"));
+    if status != 206 {
+        let meta = CacheMeta {
+            etag: header("ETag"),
+            last_modified: header("Last-Modified"),
+            length: (body_size > 0).then_some(body_size),
+        };
+        meta.write(&meta_path)?;
+    }
 
-    let file = File::create(&save_path)?;
-    let mut writer = BufWriter::new(file);
-    let mut reader = pb.wrap_read(resp.into_body().into_reader());
+    let pb = Progress::new(
+        body_size,
+        "Downloading...",
+        &options.progress,
+        options.on_progress.is_some(),
+    )?;
 
-    io::copy(&mut reader, &mut writer)?;
-    pb.finish_with_message("Downloaded!");
+    let mut hasher = hash.then(Sha256::new);
+    let file = match offset {
+        0 => File::create(&save_path)?,
+        _ => {
+            log_info!("Resuming {} at byte {offset}", save_path.display());
+            if let Some(hasher) = &mut hasher {
+                io::copy(&mut File::open(&save_path)?, hasher)?;
+            }
+            fs::OpenOptions::new().append(true).open(&save_path)?
+        }
+    };
+    let mut writer = BufWriter::with_capacity(download_buffer_size(), file);
+    let mut reader = TrackReader {
+        inner: CancelReader {
+            inner: pb.wrap_read(resp.into_body().into_reader()),
+            cancel: options.cancel.clone(),
+        },
+        downloaded: offset,
+        total: total_size,
+        hasher,
+        callback: options.on_progress.clone(),
+    };
 
-    Ok(())
+    if let Err(e) = io::copy(&mut reader, &mut writer).and_then(|_| writer.flush()) {
+        drop(writer);
+        if is_cancelled(&options.cancel) {
+            let _ = fs::remove_file(&save_path);
+            let _ = fs::remove_file(&meta_path);
+            pb.finish("Cancelled!");
+            log_info!("Download of {} cancelled", save_path.display());
+            return Err(CancelledError.into());
+        }
+        let resumable = CacheMeta::read(&meta_path)
+            .is_some_and(|m| m.length.is_some() && m.range_validator().is_some());
+        if !resumable {
+            let _ = fs::remove_file(&save_path);
+            let _ = fs::remove_file(&meta_path);
+        }
+        return Err(e.into());
+    }
+    drop(writer);
+    if !options.revalidate {
+        let _ = fs::remove_file(&meta_path);
+    }
+    pb.finish("Downloaded!");
+    log_info!("Download of {} finished", save_path.display());
+
+    Ok(DownloadResult {
+        size: reader.downloaded,
+        path: save_path,
+        filename: filename.to_string(),
+        skipped: false,
+        cache_hit: false,
+        resumed: offset > 0,
+        elapsed: started.elapsed(),
+        sha256: reader.hasher.map(|h| format!("{:x}", h.finalize())),
+    })
+}
+
+/// Picks the proxy for a download, following the rules documented on
+/// [`download_file_with`].
+///
+/// # Arguments
+/// * `url` - The URL being downloaded.
+/// * `explicit` - The proxy from [`DownloadOptions::proxy`], if any.
+///
+/// # Returns
+/// * `Ok(Some(Proxy))` - The proxy to use.
+/// * `Ok(None)` - If the host is reached directly.
+/// * `Err` - If the proxy URL is invalid.
+fn download_proxy(
+    url: &str,
+    explicit: Option<&str>,
+) -> Result<Option<ureq::Proxy>, Box<dyn Error>> {
+    let env = |name: &str| {
+        [name.to_string(), name.to_lowercase()]
+            .iter()
+            .find_map(|n| env::var(n).ok().filter(|v| !v.is_empty()))
+    };
+
+    let proxy = match explicit {
+        Some(proxy) => Some(proxy.to_string()).filter(|p| !p.is_empty()),
+        None => {
+            let (scheme, rest) = url.split_once("://").unwrap_or(("http", url));
+            let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+            let host = authority.rsplit('@').next().unwrap_or_default();
+            let host = match host.strip_prefix('[') {
+                Some(v6) => v6.split(']').next().unwrap_or_default(),
+                None => host.split(':').next().unwrap_or_default(),
+            };
+
+            let bypass = env("NO_PROXY").is_some_and(|list| no_proxy_matches(&list, host));
+            let by_scheme = match scheme.eq_ignore_ascii_case("https") {
+                true => env("HTTPS_PROXY"),
+                false => env("HTTP_PROXY"),
+            };
+            (!bypass)
+                .then(|| by_scheme.or_else(|| env("ALL_PROXY")))
+                .flatten()
+        }
+    };
+
+    proxy
+        .map(|p| ureq::Proxy::new(&p).map_err(|e| format!("Invalid proxy {p}: {e}").into()))
+        .transpose()
+}
+
+/// Tells whether `host` matches an entry of a `NO_PROXY` list.
+fn no_proxy_matches(list: &str, host: &str) -> bool {
+    let host = host.to_lowercase();
+
+    list.split(',')
+        .map(|entry| entry.trim().to_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+            let entry = entry.trim_start_matches('*').trim_start_matches('.');
+            let entry = match entry.strip_prefix('[') {
+                Some(v6) => v6.split(']').next().unwrap_or_default(),
+                None if entry.matches(':').count() == 1 => {
+                    entry.split(':').next().unwrap_or_default()
+                }
+                None => entry,
+            };
+            host == entry || host.ends_with(&format!(".{entry}"))
+        })
+}
+
+/// Tells whether a failed download attempt is worth retrying.
+///
+/// Connection failures, timeouts, dropped connections and `5xx` answers are
+/// transient; client errors such as `404`, local I/O errors and cancellation
+/// are not.
+fn is_transient(err: &(dyn Error + 'static)) -> bool {
+    if let Some(err) = err.downcast_ref::<ureq::Error>() {
+        return match err {
+            ureq::Error::StatusCode(code) => *code >= 500,
+            ureq::Error::Io(e) => is_transient(e),
+            ureq::Error::Timeout(_)
+            | ureq::Error::HostNotFound
+            | ureq::Error::ConnectionFailed
+            | ureq::Error::BodyStalled => true,
+            _ => false,
+        };
+    }
+
+    let Some(err) = err.downcast_ref::<io::Error>() else {
+        return false;
+    };
+    if let Some(inner) = err.get_ref().filter(|e| e.is::<ureq::Error>()) {
+        return is_transient(inner);
+    }
+    matches!(
+        err.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::Interrupted
+    )
+}
+
+/// Builds the [`DownloadResult`] of a file reused without transferring data.
+///
+/// # Arguments
+/// * `save_path` - The existing file.
+/// * `filename` - Its name.
+/// * `started` - When the download call began.
+/// * `hash` - Whether to hash the file, as requested by [`DownloadOptions::hash`].
+/// * `cache_hit` - Whether the server confirmed the file is current.
+///
+/// # Returns
+/// * `Ok(DownloadResult)` - The result, with `skipped` set.
+/// * `Err` - If the file cannot be read.
+fn existing_result(
+    save_path: PathBuf,
+    filename: &str,
+    started: Instant,
+    hash: bool,
+    cache_hit: bool,
+) -> Result<DownloadResult, Box<dyn Error>> {
+    let sha256 = match hash {
+        true => {
+            let mut hasher = Sha256::new();
+            io::copy(&mut File::open(&save_path)?, &mut hasher)?;
+            Some(format!("{:x}", hasher.finalize()))
+        }
+        false => None,
+    };
+
+    Ok(DownloadResult {
+        size: fs::metadata(&save_path)?.len(),
+        path: save_path,
+        filename: filename.to_string(),
+        skipped: true,
+        cache_hit,
+        resumed: false,
+        elapsed: started.elapsed(),
+        sha256,
+    })
+}
+
+/// HTTP validators of a downloaded file, kept in its `.meta` sidecar.
+///
+/// Stored as `key: value` lines (`etag`, `last-modified`, `content-length`).
+#[derive(Debug, Default)]
+struct CacheMeta {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    length: Option<u64>,
+}
+
+impl CacheMeta {
+    /// Reads a sidecar file, returning `None` if it is missing or empty.
+    fn read(path: &Path) -> Option<Self> {
+        let mut meta = Self::default();
+
+        for line in fs::read_to_string(path).ok()?.lines() {
+            let Some((key, value)) = line.split_once(": ") else {
+                continue;
+            };
+            match key {
+                "etag" => meta.etag = Some(value.to_string()),
+                "last-modified" => meta.last_modified = Some(value.to_string()),
+                "content-length" => meta.length = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        (meta.etag.is_some() || meta.last_modified.is_some() || meta.length.is_some())
+            .then_some(meta)
+    }
+
+    /// Writes the sidecar file, or removes it if the server sent neither a
+    /// validator nor a length.
+    fn write(&self, path: &Path) -> io::Result<()> {
+        if self.etag.is_none() && self.last_modified.is_none() && self.length.is_none() {
+            return match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+
+        let mut content = String::new();
+        for (key, value) in [
+            ("etag", self.etag.clone()),
+            ("last-modified", self.last_modified.clone()),
+            ("content-length", self.length.map(|l| l.to_string())),
+        ] {
+            if let Some(value) = value {
+                content.push_str(&format!("{key}: {value}\n"));
+            }
+        }
+        fs::write(path, content)
+    }
+
+    /// Returns the validator usable in `If-Range`; weak ETags are not allowed there.
+    fn range_validator(&self) -> Option<&str> {
+        match &self.etag {
+            Some(etag) if !etag.starts_with("W/") => Some(etag),
+            _ => self.last_modified.as_deref(),
+        }
+    }
 }
 
 /// Extracts a compressed bootstrap archive (tar) to a destination directory.
 ///
-/// Supports `.gz`, `.xz`, and `.zst` formats based on enabled crate features.
+/// Supports gzip, xz, zstd and bzip2 based on enabled crate features. The
+/// format is detected from the magic number at the start of the file, so the
+/// name does not matter; the extension (`.gz`, `.xz`, `.zst`, `.bz2` and
+/// their short forms) is only used when the contents are not recognized.
 ///
 /// # Arguments
 /// * `file_path` - Path to the compressed archive file.
@@ -70,38 +1209,455 @@ pub fn download_file(url: &str, dest: PathBuf, filename: &str) -> Result<(), Box
 /// # Returns
 /// * `Ok(())` - If extraction completes successfully.
 /// * `Err` - If the format is unsupported, the file is corrupted, or I/O fails.
-pub fn extract_bootstrap(file_path: PathBuf, base_destination: PathBuf) -> Result<(), Box<dyn Error>> {
+pub fn extract_bootstrap(
+    file_path: PathBuf,
+    base_destination: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    extract_bootstrap_with(file_path, base_destination, &ExtractOptions::default())?;
+    Ok(())
+}
+
+/// Same as [`extract_bootstrap`], reporting progress to a closure instead of
+/// drawing a terminal bar.
+///
+/// # Arguments
+/// * `file_path` - Path to the compressed archive file.
+/// * `base_destination` - Directory where the contents will be extracted.
+/// * `on_progress` - Called with `(bytes_read, total)` as the compressed
+///   archive is read; `total` is the size of the file.
+///
+/// # Returns
+/// * `Ok(ExtractionSummary)` - The number of entries and bytes unpacked.
+/// * `Err` - If the format is unsupported, the file is corrupted, or I/O fails.
+pub fn extract_bootstrap_with_progress(
+    file_path: PathBuf,
+    base_destination: PathBuf,
+    on_progress: impl Fn(u64, u64) + Send + Sync + 'static,
+) -> Result<ExtractionSummary, Box<dyn Error>> {
+    let options = ExtractOptions {
+        on_progress: Some(ExtractCallback::new(move |p| on_progress(p.read, p.total))),
+        ..Default::default()
+    };
+    extract_bootstrap_with(file_path, base_destination, &options)
+}
+
+/// Same as [`extract_bootstrap`], with per-call [`ExtractOptions`].
+///
+/// # Arguments
+/// * `file_path` - Path to the compressed archive file.
+/// * `base_destination` - Directory where the contents will be extracted.
+/// * `options` - Progress bar layout and other per-call settings.
+///
+/// # Returns
+/// * `Ok(ExtractionSummary)` - The number of entries and bytes unpacked.
+/// * `Err` - If the format is unsupported, the file is corrupted, or I/O fails.
+///   [`CancelledError`] if the `cancel` flag was raised,
+///   [`DestinationNotEmptyError`] if `require_empty` is set and the rootfs has entries, or
+///   [`PathTraversalError`] if an entry would land outside the rootfs, or
+///   [`SizeLimitExceededError`] if the entries exceed `max_uncompressed`.
+pub fn extract_bootstrap_with(
+    file_path: PathBuf,
+    base_destination: PathBuf,
+    options: &ExtractOptions,
+) -> Result<ExtractionSummary, Box<dyn Error>> {
+    let started = Instant::now();
     let destination = base_destination.join("rootfs");
+
+    if options.require_empty
+        && fs::read_dir(&destination).is_ok_and(|mut entries| entries.next().is_some())
+    {
+        return Err(Box::new(DestinationNotEmptyError(destination)));
+    }
+
+    let created = !destination.exists();
     fs::create_dir_all(&destination)?;
 
     let file = File::open(&file_path)?;
     let total_size = file.metadata()?.len();
 
-    let pb = ProgressBar::new(total_size);
-    pb.set_message("Extracting...");
-    pb.set_style(ProgressStyle::with_template(DOWNLOAD_TEMPLATE)?.progress_chars("##-"));
+    let pb = Progress::new(
+        total_size,
+        "Extracting...",
+        &options.progress,
+        options.on_progress.is_some(),
+    )?;
+    log_info!(
+        "Extracting {} to {}",
+        file_path.display(),
+        destination.display()
+    );
+
+    let source = archive_reader(file);
+    let source: Box<dyn Read + Send> = match &options.on_progress {
+        Some(callback) => Box::new(ExtractTrackReader {
+            inner: source,
+            read: 0,
+            total: total_size,
+            callback: callback.clone(),
+        }),
+        None => source,
+    };
+    let reader = CancelReader {
+        inner: pb.wrap_read(source),
+        cancel: options.cancel.clone(),
+    };
+    let format = match options.format {
+        Some(format) => format,
+        None => detect_format(&file_path)?,
+    };
+
+    let large = total_size >= LARGE_ARCHIVE_SIZE;
+    let window_log_max = options.zstd_window_log_max.unwrap_or(match large {
+        true => ZSTD_WINDOW_LOG_LARGE,
+        false => ZSTD_WINDOW_LOG_DEFAULT,
+    });
+
+    let decoder = open_decoder(format, reader, window_log_max)?;
+    let decoder = match options.background_decode.unwrap_or(large) {
+        true => Box::new(BackgroundReader::spawn(decoder)),
+        false => decoder,
+    };
+    let mut archive = Archive::new(decoder);
+    let mut summary = match unpack_counted(&mut archive, &destination, options) {
+        Ok(summary) => summary,
+        Err(_) if is_cancelled(&options.cancel) => {
+            if created {
+                let _ = fs::remove_dir_all(&destination);
+            }
+            pb.finish("Cancelled!");
+            log_info!("Extraction into {} cancelled", destination.display());
+            return Err(CancelledError.into());
+        }
+        Err(e)
+            if e.get_ref().is_some_and(|e| {
+                e.is::<PathTraversalError>() || e.is::<SizeLimitExceededError>()
+            }) =>
+        {
+            if created {
+                let _ = fs::remove_dir_all(&destination);
+            }
+            pb.finish("Rejected!");
+            let e = e.into_inner().expect("checked above");
+            log_info!("Extraction into {} rejected: {e}", destination.display());
+            return Err(e);
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    summary.elapsed = started.elapsed();
+
+    pb.finish("Extracted! ");
+    log_info!(
+        "Extraction into {} finished: {} entries, {} bytes in {:?}",
+        destination.display(),
+        summary.entries,
+        summary.total_bytes,
+        summary.elapsed
+    );
+    Ok(summary)
+}
+
+/// Lists the distinct top-level entries of a bootstrap archive.
+///
+/// Only the tar headers are read; entry data is skipped without being
+/// written anywhere. A single root (e.g. `alpine-minirootfs/`) means the
+/// archive keeps everything under one folder that callers may want to strip.
+/// Leading `./` components are ignored.
+///
+/// # Arguments
+/// * `file_path` - Path to the compressed archive file; the format is detected
+///   as in [`extract_bootstrap`].
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - The first path component of every entry, sorted and deduplicated.
+/// * `Err` - If the format is unsupported or disabled, or the archive is unreadable.
+pub fn archive_roots(file_path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let format = detect_format(file_path)?;
+
+    let decoder = open_decoder(
+        format,
+        archive_reader(File::open(file_path)?),
+        ZSTD_WINDOW_LOG_LARGE,
+    )?;
+    let mut roots = BTreeSet::new();
+
+    for entry in Archive::new(decoder).entries()? {
+        let entry = entry?;
+        let path = entry.path()?;
+        if let Some(Component::Normal(root)) = path
+            .components()
+            .find(|c| !matches!(c, Component::CurDir | Component::RootDir))
+        {
+            roots.insert(root.to_string_lossy().into_owned());
+        }
+    }
+
+    Ok(roots.into_iter().collect())
+}
+
+/// Unpacks every entry of `archive` into `dst`, counting entries and bytes.
+///
+/// Entries are unpacked one by one rather than through `tar::Archive::unpack`,
+/// so each target is checked against `dst` first: a path containing `..`, a
+/// write through a symlink leading outside `dst`, or a hard link to a file
+/// outside it fails with [`PathTraversalError`]. Absolute paths are taken as
+/// relative to `dst`. Directories are applied last, deepest first, so
+/// restrictive directory permissions never block their contents.
+///
+/// Entry sizes are added up before each entry is written, so an archive
+/// exceeding `max_uncompressed` fails with [`SizeLimitExceededError`]
+/// without writing the entry that crosses the limit.
+///
+/// # Arguments
+/// * `archive` - The tar archive, wrapping the decompressed stream.
+/// * `dst` - The directory to unpack into; it must already exist.
+/// * `options` - Supplies `strip_components` and `max_uncompressed`.
+///
+/// # Returns
+/// The [`ExtractionSummary`] of the unpacked entries.
+fn unpack_counted<R: Read>(
+    archive: &mut Archive<R>,
+    dst: &Path,
+    options: &ExtractOptions,
+) -> io::Result<ExtractionSummary> {
+    let strip = options.strip_components;
+    let dst = &dst.canonicalize().unwrap_or_else(|_| dst.to_path_buf());
+    let mut summary = ExtractionSummary::default();
+    let mut directories = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let Some(target) = entry_target(dst, &entry.path()?, strip)? else {
+            continue;
+        };
+        summary.entries += 1;
+        summary.total_bytes += entry.size();
+        if let Some(limit) = options.max_uncompressed
+            && summary.total_bytes > limit
+        {
+            return Err(io::Error::other(SizeLimitExceededError(limit)));
+        }
+
+        if entry.header().entry_type() == EntryType::Directory {
+            directories.push((target, entry));
+        } else {
+            unpack_entry(&mut entry, dst, &target, strip)?;
+        }
+    }
+
+    directories.sort_by(|a, b| b.0.cmp(&a.0));
+    for (target, mut dir) in directories {
+        unpack_entry(&mut dir, dst, &target, strip)?;
+    }
+
+    Ok(summary)
+}
+
+/// Maps an entry path to its location under `dst`.
+///
+/// Root and `.` components are ignored, then `strip` leading components are
+/// dropped.
+///
+/// # Returns
+/// * `Ok(Some(PathBuf))` - The target path.
+/// * `Ok(None)` - If nothing is left of the path after stripping.
+/// * `Err` - A [`PathTraversalError`] if the path contains `..`.
+fn entry_target(dst: &Path, path: &Path, strip: usize) -> io::Result<Option<PathBuf>> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part),
+            Component::ParentDir => return Err(traversal(path)),
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+        }
+    }
+
+    let relative: PathBuf = parts.into_iter().skip(strip).collect();
+    Ok((!relative.as_os_str().is_empty()).then(|| dst.join(relative)))
+}
+
+/// Writes one entry to `target`, creating its parent directories.
+///
+/// # Arguments
+/// * `entry` - The archive entry.
+/// * `dst` - The canonical extraction root.
+/// * `target` - Where the entry goes, from [`entry_target`].
+/// * `strip` - Leading components dropped, also applied to hard link targets.
+fn unpack_entry<R: Read>(
+    entry: &mut tar::Entry<'_, R>,
+    dst: &Path,
+    target: &Path,
+    strip: usize,
+) -> io::Result<()> {
+    let parent = target.parent().unwrap_or(dst);
+    let missing: Vec<&Path> = parent
+        .ancestors()
+        .take_while(|p| p.symlink_metadata().is_err())
+        .collect();
+    for dir in missing.into_iter().rev() {
+        check_inside(dst, dir.parent().unwrap_or(dst), target)?;
+        fs::create_dir_all(dir)?;
+    }
+    check_inside(dst, parent, target)?;
 
-    let reader = pb.wrap_read(BufReader::with_capacity(64 * 1024, file));
-    let ext = file_path.extension().and_then(|s| s.to_str()).unwrap_or("");
+    match entry.header().entry_type() {
+        EntryType::Link => {}
+        EntryType::Directory => {
+            // An existing directory is reused and its permissions updated,
+            // which would follow a symlink planted at the same path.
+            if target.symlink_metadata().is_ok() {
+                check_inside(dst, target, target)?;
+            }
+            return entry.unpack(target).map(|_| ());
+        }
+        _ => return entry.unpack(target).map(|_| ()),
+    }
 
-    let decoder: Box<dyn Read> = match ext {
+    let source = match entry.link_name()? {
+        Some(name) => entry_target(dst, &name, strip)?,
+        None => None,
+    }
+    .ok_or_else(|| {
+        io::Error::other(format!("Invalid hard link target for {}", target.display()))
+    })?;
+    check_inside(dst, &source, target)?;
+    if target.symlink_metadata().is_ok() {
+        fs::remove_file(target)?;
+    }
+    fs::hard_link(&source, target)
+}
+
+/// Fails with a [`PathTraversalError`] for `entry` unless `path` resolves,
+/// through any symlinks, to a location inside `dst`.
+fn check_inside(dst: &Path, path: &Path, entry: &Path) -> io::Result<()> {
+    match path.canonicalize()?.starts_with(dst) {
+        true => Ok(()),
+        false => Err(traversal(entry)),
+    }
+}
+
+/// Wraps a [`PathTraversalError`] for `entry` in an I/O error, so it crosses
+/// the unpack loop and is unwrapped again by [`extract_bootstrap_with`].
+fn traversal(entry: &Path) -> io::Error {
+    io::Error::other(PathTraversalError(entry.to_path_buf()))
+}
+
+/// Opens the raw byte source for a local archive.
+///
+/// With the `mmap` feature, the file is memory-mapped and read sequentially
+/// from the mapping. This avoids one `read(2)` syscall and one buffer copy per
+/// 64 KiB chunk, which mainly pays off for multi-GB archives on fast storage
+/// with a fast decoder (zstd, plain gzip); for xz the decoder dominates and the
+/// gain is negligible. If mapping fails (special files, exotic filesystems),
+/// the streaming `BufReader` path is used instead, sized by
+/// [`set_extract_buffer_size`].
+///
+/// # Arguments
+/// * `file` - The opened archive file.
+///
+/// # Returns
+/// A boxed reader yielding the archive bytes.
+fn archive_reader(file: File) -> Box<dyn Read + Send> {
+    #[cfg(feature = "mmap")]
+    {
+        // SAFETY: the mapping is read-only and private to this extraction; the
+        // archive is not expected to be modified while it is being unpacked.
+        if let Ok(map) = unsafe { memmap2::Mmap::map(&file) } {
+            let _ = map.advise(memmap2::Advice::Sequential);
+            return Box::new(io::Cursor::new(map));
+        }
+    }
+
+    Box::new(BufReader::with_capacity(extract_buffer_size(), file))
+}
+
+/// Wraps the raw archive bytes in the decompressor for `format`.
+///
+/// # Arguments
+/// * `format` - The compression of the archive.
+/// * `reader` - The compressed byte source.
+/// * `window_log_max` - Largest zstd window accepted, as a power of two.
+///
+/// # Returns
+/// * `Ok(Box<dyn Read + Send>)` - A reader yielding the uncompressed tar stream.
+/// * `Err` - If the crate feature for `format` is disabled, or the decoder fails to start.
+fn open_decoder<R: Read + Send + 'static>(
+    format: ArchiveFormat,
+    reader: R,
+    window_log_max: u32,
+) -> Result<Box<dyn Read + Send>, Box<dyn Error>> {
+    match format {
         #[cfg(feature = "gz")]
-        "gz" => Box::new(flate2::read::GzDecoder::new(reader)),
+        ArchiveFormat::Gz => Ok(Box::new(flate2::read::GzDecoder::new(reader))),
 
         #[cfg(feature = "xz")]
-        "xz" => Box::new(xz2::read::XzDecoder::new(reader)),
+        ArchiveFormat::Xz => Ok(Box::new(xz2::read::XzDecoder::new(reader))),
 
         #[cfg(feature = "zst")]
-        "zst" | "zstd" => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        ArchiveFormat::Zst => {
+            let mut decoder = zstd::stream::read::Decoder::new(reader)?;
+            decoder.window_log_max(window_log_max)?;
+            Ok(Box::new(decoder))
+        }
 
-        _ => {
-            return Err(format!("Unsupported or disabled format: .{ext}",).into());
+        #[cfg(feature = "bz2")]
+        ArchiveFormat::Bz2 => Ok(Box::new(bzip2::read::MultiBzDecoder::new(reader))),
+
+        ArchiveFormat::Tar => Ok(Box::new(reader)),
+
+        #[allow(unreachable_patterns)]
+        disabled => {
+            let _ = (reader, window_log_max);
+            Err(format!(
+                "Format .{ext} is disabled; enable the `{ext}` crate feature",
+                ext = disabled.extension()
+            )
+            .into())
         }
-    };
+    }
+}
 
-    let mut archive = Archive::new(decoder);
-    archive.unpack(&destination)?;
+/// Detects the compression of an archive from its magic number, or from its
+/// extension when the first bytes match no known format.
+///
+/// # Arguments
+/// * `file_path` - Path to the archive file.
+///
+/// # Returns
+/// * `Ok(ArchiveFormat)` - The detected format.
+/// * `Err` - If the file cannot be read, or neither its contents nor its
+///   extension name a known format.
+fn detect_format(file_path: &Path) -> Result<ArchiveFormat, Box<dyn Error>> {
+    let mut header = Vec::with_capacity(MAGIC_LEN);
+    File::open(file_path)?
+        .take(MAGIC_LEN as u64)
+        .read_to_end(&mut header)?;
 
-    pb.finish_with_message("Extracted! ");
-    Ok(())
+    if let Some(format) = ArchiveFormat::from_magic(&header) {
+        return Ok(format);
+    }
+    let ext = archive_extension(file_path);
+    ArchiveFormat::from_extension(ext)
+        .ok_or_else(|| format!("Unsupported or disabled format: .{ext}").into())
+}
+
+/// Resolves the compression extension of an archive path.
+///
+/// Short tarball aliases are normalized to their compression suffix so they
+/// share the same decoder arm as the `.tar.*` forms:
+/// `tgz` → `gz`, `txz` → `xz`, `tzst` → `zst`, `tbz2`/`tbz` → `bz2`.
+///
+/// # Arguments
+/// * `file_path` - Path to the archive file.
+///
+/// # Returns
+/// The normalized extension, or an empty string if there is none.
+fn archive_extension(file_path: &Path) -> &str {
+    match file_path.extension().and_then(|s| s.to_str()).unwrap_or("") {
+        "tgz" => "gz",
+        "txz" => "xz",
+        "tzst" => "zst",
+        "tbz2" | "tbz" => "bz2",
+        ext => ext,
+    }
 }
@@ -14,39 +14,158 @@
 //! happens to those changes (discard, preserve, commit, or atomic commit).
 
 unsafe extern "C" {
-    /// Retrieves the real user ID of the calling process.
-    /// Used to map the host user to the sandbox environment.
-    fn getuid() -> u32;
-
-    /// Retrieves the effective user ID of the calling process.
-    /// Used to determine the current privilege level before entering the sandbox.
-    fn geteuid() -> u32;
+    /// Sends a signal to a process, or to a whole process group when `pid` is negative.
+    /// Used to stop every guest process when a run exceeds its timeout.
+    fn kill(pid: i32, sig: i32) -> i32;
+    /// Waits for a child process, or for any child in a process group when
+    /// `pid` is negative. Used to reap the guest processes left after a timeout.
+    fn waitpid(pid: i32, status: *mut i32, options: i32) -> i32;
+    /// Duplicates a file descriptor onto a given number.
+    /// Used to hand the seccomp program to Bubblewrap on a known descriptor.
+    fn dup2(oldfd: i32, newfd: i32) -> i32;
+    /// Manipulates a file descriptor; here only to clear its close-on-exec flag.
+    fn fcntl(fd: i32, cmd: i32, ...) -> i32;
+    /// Writes to a file descriptor without allocating.
+    /// Used by the child to join its cgroup between fork and exec.
+    fn write(fd: i32, buf: *const u8, count: usize) -> isize;
 }
 
-use crate::{default_rootfs, safe_home, sandbox_tool, tool_target, USE_BWRAP, USE_PROOT};
+use crate::bind::BindMount;
+use crate::cgroup::Cgroup;
+use crate::diagnose::{diagnose_rootfs_shell, resolve_in_rootfs};
+use crate::macros::{log_debug, log_warn};
+use crate::shell::shell_join;
+use crate::{
+    host_euid, host_uid, resolved_rootfs, safe_home, sandbox_tool, temp_cache, tool_target,
+    USE_BWRAP, USE_PROOT,
+};
 use overlayfs_fuse::{CommitFilter, InodeMode, OverlayAction, OverlayFS};
 use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::os::fd::AsRawFd;
 use std::os::unix;
-use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
-use std::{fmt, fs};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::OnceLock;
+use std::time::Duration;
+use std::{env, fmt, fs, thread};
+
+/// Shell used in the guest when `SandBoxConfig::shell` is unset.
+const DEFAULT_SHELL: &str = "/bin/sh";
+
+/// Signal asking the guest processes to terminate.
+const SIGTERM: i32 = 15;
+
+/// Signal killing the guest processes that ignored `SIGTERM`.
+const SIGKILL: i32 = 9;
+
+/// Time given to the guest to exit after `SIGTERM` before `SIGKILL` is sent.
+const KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// Default target of the `/etc/mtab` symlink fixed by `SandBoxConfig::fix_mtab`.
+pub(crate) const MTAB_TARGET: &str = "/proc/self/mounts";
+
+/// Descriptor number the seccomp program is passed to Bubblewrap on.
+const SECCOMP_FD: i32 = 10;
+
+/// `fcntl` command that sets the descriptor flags, such as `FD_CLOEXEC`.
+const F_SETFD: i32 = 2;
 
 /// Custom error type for cases where the RootFS directory is missing.
 #[derive(Debug)]
 pub struct RootfsNotFoundError(pub PathBuf);
 
+/// Custom error type for a guest shell that is missing from the RootFS or is
+/// not executable.
+///
+/// Holds the host path where the shell was expected.
+#[derive(Debug)]
+pub struct ShellNotFoundError(pub PathBuf);
+
+/// Custom error type for a RootFS that lacks essential files or directories.
+///
+/// Holds the inspected RootFS path and the list of missing entries,
+/// relative to the RootFS root.
+#[derive(Debug)]
+pub struct InvalidRootfsError(pub PathBuf, pub Vec<&'static str>);
+
+/// Custom error type for an option the selected sandbox tool cannot honor.
+///
+/// Holds the name of the offending `SandBoxConfig` field and the tool name.
+#[derive(Debug)]
+pub struct UnsupportedOptionError(pub &'static str, pub String);
+
+/// Custom error type for a sandbox stopped because it exceeded its timeout.
+///
+/// Holds the configured timeout.
+#[derive(Debug)]
+pub struct TimedOutError(pub Duration);
+
+/// Entries that must exist for a RootFS to be considered usable.
+///
+/// * `bin/sh` - The shell used to launch every sandboxed command.
+/// * `etc` - System configuration directory (passwd, resolv.conf, mtab, ...).
+/// * `usr` - Base directory for binaries and libraries.
+pub const REQUIRED_ROOTFS_PATHS: &[&str] = &["bin/sh", "etc", "usr"];
+
+/// Directories for a Bubblewrap kernel overlay over the rootfs.
+///
+/// The rootfs is the read-only lower layer; every write inside the sandbox
+/// lands in `upper`. Both directories are created if missing and must live on
+/// the same filesystem. Requires Bubblewrap 0.10 or newer.
+#[derive(Clone, Debug)]
+pub struct OverlayConfig {
+    /// Directory receiving the changes made inside the sandbox.
+    pub upper: PathBuf,
+    /// Scratch directory required by overlayfs, on the same filesystem as `upper`.
+    pub work: PathBuf,
+}
+
+/// How `/dev`, `/proc`, `/sys`, `/tmp` and `/run` are provided to the guest.
+///
+/// Both tools honor each mode as far as they can, but some differences cannot
+/// be removed:
+/// * PRoot never mounts anything; it only binds host paths, so `/proc` is
+///   always the host one (with `/proc/self` and friends emulated per guest
+///   process) and device nodes are always the host nodes.
+/// * With `-R` (the default without `secure_rootfs`), PRoot itself binds the
+///   host `/tmp` and `/run`, so [`PseudoFs::Private`] cannot hide them.
+/// * With `secure_rootfs` (`-S`), PRoot never gets the host `/tmp` and `/run`
+///   binds, even in [`PseudoFs::Host`] mode, so secure mode stays isolated.
+/// * Bubblewrap keeps `/sys` a read-only bind of the host in both modes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PseudoFs {
+    /// Binds the host `/dev`, `/proc`, `/sys`, `/tmp` and `/run` (default).
+    /// PRoot gets explicit binds too, except for `/tmp` and `/run` under
+    /// `secure_rootfs` (`-S`).
+    #[default]
+    Host,
+    /// Gives the guest its own instances where the tool can: Bubblewrap mounts
+    /// a new `/proc`, a minimal `/dev` and empty tmpfs `/tmp` and `/run`;
+    /// PRoot binds only the host `/dev`, `/proc` and `/sys`.
+    Private,
+}
+
 /// Configuration structure for defining how the sandbox should run.
 #[derive(Clone)]
 pub struct SandBoxConfig {
     /// Path to the RootFS directory.
     pub rootfs: PathBuf,
-    /// Command to be executed inside the sandbox.
+    /// Command to be executed inside the sandbox, interpreted by `shell -c`.
+    /// Use `run_argv` instead when it includes untrusted input.
     pub run_cmd: String,
     /// Which tool to use (proot or bwrap).
     pub rootfs_tool: String,
     /// Path to the sandbox tool binary.
     pub tool_target: PathBuf,
-    /// Custom bind mounts provided by the user.
+    /// Custom bind mounts provided by the user, as raw tool arguments split on
+    /// whitespace, so paths containing spaces break. Deprecated in favor of
+    /// `binds`; kept for one release as a fallback for raw extra flags.
     pub args_bind: String,
     /// If true, simulates a root user environment.
     pub use_root: bool,
@@ -70,11 +189,201 @@ pub struct SandBoxConfig {
     pub overlay_as_home: bool,
     /// If true, restricts the sandbox to essential rootfs paths only.
     pub secure_rootfs: bool,
+    /// If true, copies the host `resolv.conf`, `hosts` and `host.conf` into the
+    /// rootfs `/etc` before launch instead of binding the host files read-only,
+    /// giving the guest a private, writable copy.
+    pub copy_net_files: bool,
+    /// If true, ensures `/etc/mtab` in the rootfs is a symlink to
+    /// `mtab_target` before launch, for either tool. Skipped when
+    /// `secure_rootfs` is set; disable it to leave the rootfs untouched. A
+    /// failure to fix it aborts the run with the underlying error.
+    pub fix_mtab: bool,
+    /// Guest path the `/etc/mtab` symlink points to when `fix_mtab` is set.
+    /// Defaults to `/proc/self/mounts`.
+    pub mtab_target: Option<PathBuf>,
+    /// Extra arguments inserted verbatim into the tool's command line, after
+    /// the generated options and before the guest command. They are passed
+    /// unescaped and unvalidated; their correctness is the caller's
+    /// responsibility. Use this for tool flags not modeled by this struct.
+    pub raw_tool_args: Vec<String>,
+    /// Custom `PS1` for interactive guest shells, e.g. `"(sandbox) $ "`.
+    /// Falls back to `"$ "`, or `"# "` when `use_root` is set.
+    pub prompt: Option<String>,
+    /// Custom hostname for the guest, seen by `uname -n` and in generated
+    /// `/etc/hostname` and `/etc/hosts` files. Bubblewrap only, through a new
+    /// UTS namespace: PRoot cannot change the kernel hostname and rejects it.
+    pub hostname: Option<String>,
+    /// Mounts the rootfs as the lower layer of a kernel overlay (bwrap
+    /// `--overlay-src`/`--overlay`), so several sandboxes can share one base
+    /// rootfs without modifying it. Unlike `use_overlay`, no FUSE mount is
+    /// involved. Not supported by PRoot.
+    pub overlay: Option<OverlayConfig>,
+    /// If true, the binds in `args_bind` whose host source does not exist are
+    /// dropped with a warning instead of making the tool abort the launch.
+    /// Disable it to pass `args_bind` through untouched.
+    pub check_bind_sources: bool,
+    /// Device nodes to expose in the guest, e.g. `/dev/kvm` or `/dev/net/tun`.
+    /// Each existing node is `--dev-bind`ed (bwrap) or `--bind`ed (proot);
+    /// missing ones are skipped with a warning. Paths must be under `/dev`.
+    pub devices: Vec<PathBuf>,
+    /// How the pseudo-filesystems are provided; see [`PseudoFs`] for the
+    /// differences between the tools.
+    pub pseudo_fs: PseudoFs,
+    /// Structured bind mounts, passed to the tool as separate arguments so
+    /// paths may contain spaces. Usually built with [`crate::BindSet`].
+    /// Missing sources are skipped like `args_bind` ones when
+    /// `check_bind_sources` is set; optional mounts are always skipped quietly.
+    pub binds: Vec<BindMount>,
+    /// Maximum run time of the guest. On expiry the whole process group
+    /// receives `SIGTERM`, then `SIGKILL` two seconds later, so processes left
+    /// behind by the guest do not leak, and `run` returns [`TimedOutError`].
+    /// The tool runs in its own process group, so an interactive guest cannot
+    /// read from the terminal while this is set.
+    pub timeout: Option<Duration>,
+    /// Name exported as `USER` and `LOGNAME` in the guest, e.g. a user baked
+    /// into the rootfs. Only the environment changes: the uid/gid still follow
+    /// `use_root`, and the host `/etc/passwd` bound into the guest is left as
+    /// is, so `id -un` may report another name. Falls back to `root` when
+    /// `use_root` is set, and to leaving both variables untouched otherwise.
+    pub user: Option<String>,
+    /// If true, `run` prints the shell-quoted command line it would execute
+    /// (see [`SandBox::command_string`]) and returns a successful status
+    /// without mounting, writing or spawning anything. `run_captured` returns
+    /// the line as its stdout instead of printing it.
+    pub dry_run: bool,
+    /// Extra environment variables for the guest, e.g. `("LANG", "C.UTF-8")`.
+    /// Each pair is passed as a single argument, so values may hold spaces or
+    /// any other character. They are applied after the built-in ones, so a
+    /// key such as `PATH`, `SHELL`, `PS1`, `USER` or `UID` overrides the
+    /// generated value; on duplicate keys, the last pair wins.
+    pub env: Vec<(String, String)>,
+    /// Absolute guest directory the command starts in (`--chdir` for bwrap,
+    /// `-w` for proot); `/` when unset. It must exist in the rootfs, in the
+    /// bound home directory or under the destination of one of `binds`.
+    pub workdir: Option<PathBuf>,
+    /// Guest shell exported as `SHELL` and used to run `run_cmd` with `-c`
+    /// (or started interactively when `run_cmd` is empty), e.g. `/bin/bash`.
+    /// Defaults to `/bin/sh`. It must exist in the rootfs; `run` checks it
+    /// before launching.
+    pub shell: Option<String>,
+    /// Program and arguments executed directly, without the shell wrapper,
+    /// e.g. `["python3", "script with spaces.py"]`. Each element reaches the
+    /// guest verbatim, so quotes and `$` in untrusted input stay literal.
+    /// Mutually exclusive with `run_cmd`.
+    pub run_argv: Option<Vec<String>>,
+    /// If false, the guest gets its own network namespace with only a
+    /// loopback interface, so it cannot reach the host network
+    /// (`--unshare-net`). Bubblewrap only: PRoot has no namespaces and
+    /// rejects it. Defaults to true.
+    pub share_net: bool,
+    /// If true, the guest root is remounted read-only once every mount is in
+    /// place, so writes to `/` fail with `EROFS` while `/tmp`, `/run`, the
+    /// home directory and the other binds stay writable. Bubblewrap only.
+    /// `fix_mtab` still applies, since it edits the rootfs from the host
+    /// before launch.
+    pub readonly_rootfs: bool,
+    /// If true, binds the host `/dev/dri` card and render nodes for GL and
+    /// Vulkan, for both tools; skipped if the directory is absent. As an
+    /// explicit request, it applies even with `ignore_extra_bind` or
+    /// `secure_rootfs` set, which only disable the fonts and themes.
+    pub bind_gpu: bool,
+    /// If true, forwards the PulseAudio (`$XDG_RUNTIME_DIR/pulse/native`) and
+    /// PipeWire (`$XDG_RUNTIME_DIR/pipewire-0`) sockets, for both tools, and
+    /// points `PULSE_SERVER` and `XDG_RUNTIME_DIR` at them. Sockets missing
+    /// on the host are skipped silently.
+    pub bind_audio: bool,
+    /// If true, connects GUI apps to the host display, for both tools: binds
+    /// `/tmp/.X11-unix` read-only and forwards `DISPLAY` when X11 is in use,
+    /// and binds `$XDG_RUNTIME_DIR/$WAYLAND_DISPLAY` and sets
+    /// `WAYLAND_DISPLAY` and `XDG_RUNTIME_DIR` when Wayland is. Only the
+    /// servers detected on the host are forwarded.
+    pub bind_display: bool,
+    /// Compiled seccomp BPF program applied to the guest (`--seccomp`). The
+    /// file is opened before launch and handed to Bubblewrap on a numbered
+    /// descriptor. PRoot has no seccomp support and ignores it with a warning.
+    pub seccomp: Option<PathBuf>,
+    /// Maximum memory of the tool and the guest, in bytes (`memory.max`).
+    /// Like `cpu_quota`, it runs the tool in a transient cgroup v2 group
+    /// created next to the current one, so the hierarchy must be delegated
+    /// to the user; `run` fails with a clear error otherwise.
+    pub memory_limit: Option<u64>,
+    /// CPU time available to the tool and the guest, in cores, e.g. `1.5`
+    /// (`cpu.max`).
+    pub cpu_quota: Option<f64>,
+}
+
+/// Outcome of probing the optional host paths for a configuration.
+///
+/// Returned by [`SandBox::extra_binds`]; lets a verbose or health-check mode
+/// show which fonts, themes and cursor directories are visible in the guest.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtraBindReport {
+    /// Host paths that exist and are bound into the guest.
+    pub applied: Vec<String>,
+    /// Candidate paths left out, because they are missing on the host or
+    /// because `ignore_extra_bind`/`secure_rootfs` disabled optional binds.
+    pub skipped: Vec<String>,
 }
 
 /// Core structure for sandbox operations.
 pub struct SandBox;
 
+/// Host network files shared with the guest, either bound read-only or copied.
+const NET_FILES: [&str; 3] = ["/etc/host.conf", "/etc/hosts", "/etc/resolv.conf"];
+
+/// Name service files bound into the guest by every tool, even with `secure_rootfs`.
+const NSS_CORE_FILES: [&str; 1] = ["/etc/nsswitch.conf"];
+
+/// Additional name service and identity files bound unless `secure_rootfs` is set.
+const NSS_EXTRA_FILES: [&str; 6] = [
+    "/etc/hosts.equiv",
+    "/etc/netgroup",
+    "/etc/networks",
+    "/etc/localtime",
+    "/etc/passwd",
+    "/etc/group",
+];
+
+/// Optional host paths (sound, fonts, themes) bound when they exist.
+const EXTRA_PATHS: [&str; 6] = [
+    "/etc/asound.conf",
+    "/etc/fonts",
+    "/usr/share/font-config",
+    "/usr/share/fontconfig",
+    "/usr/share/fonts",
+    "/usr/share/themes",
+];
+
+/// Host directory of the DRM card and render nodes bound by `bind_gpu`.
+const GPU_DIR: &str = "/dev/dri";
+
+/// Audio server sockets bound by `bind_audio`, relative to `$XDG_RUNTIME_DIR`.
+const AUDIO_SOCKETS: [&str; 2] = ["pulse/native", "pipewire-0"];
+
+/// Host directory of the X11 server sockets bound by `bind_display`.
+const X11_SOCKET_DIR: &str = "/tmp/.X11-unix";
+
+/// Cached result of the icon theme scan performed by [`SandBox::cursor_dirs`].
+static CURSOR_DIRS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Counter making the hostname file directories unique across runs of the
+/// same process.
+static NEXT_HOSTNAME_DIR: AtomicU64 = AtomicU64::new(0);
+
+/// Counter making the temporary `etc/mtab` symlink names unique across
+/// concurrent runs of the same process.
+static NEXT_MTAB_TMP: AtomicU64 = AtomicU64::new(0);
+
+/// The hostname file directory of one run, removed when dropped.
+struct HostnameDir<'a>(&'a Path);
+
+impl Drop for HostnameDir<'_> {
+    /// Removes the directory, whichever way the run ended.
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(self.0);
+    }
+}
+
 impl fmt::Display for RootfsNotFoundError {
     /// Formats the error message for the missing RootFS directory.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -88,6 +397,91 @@ impl fmt::Display for RootfsNotFoundError {
 /// into generic error handling containers like `Box<dyn Error>`.
 impl Error for RootfsNotFoundError {}
 
+impl fmt::Display for ShellNotFoundError {
+    /// Formats the error message with the expected shell path.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Shell not found or not executable at: {:?}; the rootfs may be \
+             incomplete, or set `shell` to one installed in the guest",
+            self.0
+        )
+    }
+}
+
+/// Implements the standard Error trait for ShellNotFoundError.
+impl Error for ShellNotFoundError {}
+
+impl fmt::Display for InvalidRootfsError {
+    /// Formats the error message listing every missing RootFS entry.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid rootfs at {:?}: missing {}",
+            self.0,
+            self.1.join(", ")
+        )
+    }
+}
+
+/// Implements the standard Error trait for InvalidRootfsError.
+impl Error for InvalidRootfsError {}
+
+impl fmt::Display for UnsupportedOptionError {
+    /// Formats the error message naming the option and the tool.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Option '{}' is not supported by {}", self.0, self.1)
+    }
+}
+
+/// Implements the standard Error trait for UnsupportedOptionError.
+impl Error for UnsupportedOptionError {}
+
+impl fmt::Display for TimedOutError {
+    /// Formats the error message with the exceeded timeout.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sandbox timed out after {:?}", self.0)
+    }
+}
+
+/// Implements the standard Error trait for TimedOutError.
+impl Error for TimedOutError {}
+
+/// Checks that an extracted RootFS contains the essential entries listed in
+/// [`REQUIRED_ROOTFS_PATHS`].
+///
+/// Intended to run between [`crate::extract_bootstrap`] and
+/// [`crate::success_finish_setup`], so a broken archive is reported at setup
+/// time instead of at the first `run`.
+///
+/// # Arguments
+/// * `path` - The base directory given to `extract_bootstrap`; the RootFS
+///   itself is expected in its `rootfs` subdirectory.
+///
+/// # Returns
+/// * `Ok(())` - If every required entry exists.
+/// * `Err` - A [`RootfsNotFoundError`] if the RootFS directory is absent, or an
+///   [`InvalidRootfsError`] listing what is missing.
+pub fn validate_rootfs(path: &Path) -> Result<(), Box<dyn Error>> {
+    let rootfs = path.join("rootfs");
+
+    if !rootfs.is_dir() {
+        return Err(Box::new(RootfsNotFoundError(rootfs)));
+    }
+
+    let missing: Vec<&'static str> = REQUIRED_ROOTFS_PATHS
+        .iter()
+        .copied()
+        .filter(|entry| fs::symlink_metadata(rootfs.join(entry)).is_err())
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(Box::new(InvalidRootfsError(rootfs, missing)));
+    }
+
+    Ok(())
+}
+
 impl Default for SandBoxConfig {
     /// Provides the default configuration for the sandbox.
     ///
@@ -95,7 +489,7 @@ impl Default for SandBoxConfig {
     /// A `SandBoxConfig` instance populated with global defaults from the `init` module.
     fn default() -> Self {
         Self {
-            rootfs: default_rootfs(),
+            rootfs: resolved_rootfs(),
             run_cmd: String::default(),
             rootfs_tool: sandbox_tool(),
             tool_target: tool_target(),
@@ -108,7 +502,113 @@ impl Default for SandBoxConfig {
             overlay_upper: None,
             overlay_as_home: false,
             secure_rootfs: false,
+            copy_net_files: false,
+            fix_mtab: true,
+            mtab_target: None,
+            raw_tool_args: Vec::new(),
+            prompt: None,
+            hostname: None,
+            overlay: None,
+            check_bind_sources: true,
+            devices: Vec::new(),
+            pseudo_fs: PseudoFs::Host,
+            binds: Vec::new(),
+            timeout: None,
+            user: None,
+            dry_run: false,
+            env: Vec::new(),
+            workdir: None,
+            shell: None,
+            run_argv: None,
+            share_net: true,
+            readonly_rootfs: false,
+            bind_gpu: false,
+            bind_audio: false,
+            bind_display: false,
+            seccomp: None,
+            memory_limit: None,
+            cpu_quota: None,
+        }
+    }
+}
+
+impl SandBoxConfig {
+    /// Checks that the configuration can be honored by the selected tool.
+    ///
+    /// Runs automatically at the start of [`SandBox::run`], before any overlay
+    /// is mounted, but can also be called up front to report problems early.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the tool is known and supports every enabled option.
+    /// * `Err` - If the tool is unknown, the hostname is malformed, a device lies
+    ///   outside `/dev`, an environment variable name is empty or holds `=`, a
+    ///   resource limit is not positive, the working directory is relative, `run_argv` is empty or set together with
+    ///   `run_cmd`, or an
+    ///   [`UnsupportedOptionError`] naming the first option that the tool cannot honor.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(name) = &self.hostname
+            && (name.is_empty() || name.len() > 64 || name.contains(char::is_whitespace))
+        {
+            return Err(format!("Invalid hostname: {name:?}").into());
+        }
+
+        if let Some(device) = self
+            .devices
+            .iter()
+            .find(|d| !d.starts_with("/dev/") || d.components().any(|c| c == Component::ParentDir))
+        {
+            return Err(format!("Device path is not under /dev: {}", device.display()).into());
+        }
+
+        if let Some((key, _)) = self
+            .env
+            .iter()
+            .find(|(k, v)| k.is_empty() || k.contains(['=', '\0']) || v.contains('\0'))
+        {
+            return Err(format!("Invalid environment variable name: {key:?}").into());
+        }
+
+        if self.memory_limit == Some(0) {
+            return Err("Memory limit must be greater than zero".into());
         }
+
+        if let Some(cores) = self.cpu_quota.filter(|c| !c.is_finite() || *c <= 0.0) {
+            return Err(format!("Invalid CPU quota: {cores}").into());
+        }
+
+        if let Some(workdir) = self.workdir.as_ref().filter(|w| !w.is_absolute()) {
+            return Err(
+                format!("Working directory must be absolute: {}", workdir.display()).into(),
+            );
+        }
+
+        match &self.run_argv {
+            Some(argv) if argv.is_empty() => return Err("run_argv must not be empty".into()),
+            Some(_) if !self.run_cmd.is_empty() => {
+                return Err("run_cmd and run_argv are mutually exclusive".into());
+            }
+            _ => {}
+        }
+
+        let unsupported: &[(&'static str, bool)] = match self.rootfs_tool.as_str() {
+            USE_PROOT => &[
+                ("overlay", self.overlay.is_some()),
+                ("hostname", self.hostname.is_some()),
+                ("share_net", !self.share_net),
+                ("readonly_rootfs", self.readonly_rootfs),
+            ],
+            USE_BWRAP => &[],
+            other => return Err(format!("Unsupported rootfs command: {other}").into()),
+        };
+
+        if let Some((option, _)) = unsupported.iter().find(|(_, enabled)| *enabled) {
+            return Err(Box::new(UnsupportedOptionError(
+                option,
+                self.rootfs_tool.clone(),
+            )));
+        }
+
+        Ok(())
     }
 }
 
@@ -127,16 +627,85 @@ impl SandBox {
     /// * `config` - A `SandBoxConfig` containing all execution parameters.
     ///
     /// # Returns
-    /// * `Ok(())` - If the process starts and exits successfully.
-    /// * `Err` - If the rootfs is missing, the overlay fails to mount, or the process errors.
-    pub fn run(mut config: SandBoxConfig) -> Result<(), Box<dyn Error>> {
-        let base_path = config.rootfs.clone();
+    /// * `Ok(ExitStatus)` - The exit status of the tool, which forwards the one
+    ///   of the guest command; a non-zero or signal exit is not an error, so
+    ///   callers can propagate it with `std::process::exit`.
+    /// * `Err` - If the configuration is invalid for the tool (see [`SandBoxConfig::validate`]),
+    ///   the rootfs is missing, the overlay fails to mount, the tool cannot be
+    ///   spawned, or it exceeds `timeout` ([`TimedOutError`]).
+    pub fn run(config: SandBoxConfig) -> Result<ExitStatus, Box<dyn Error>> {
+        Self::run_with_stdio(config, false).map(|output| output.status)
+    }
+
+    /// Executes the sandbox like [`Self::run`], capturing the guest output.
+    ///
+    /// stdout and stderr are piped and read concurrently, so large outputs
+    /// cannot deadlock; stdin is connected to `/dev/null`. Log messages of this
+    /// crate still go to the host terminal.
+    ///
+    /// # Arguments
+    /// * `config` - A `SandBoxConfig` containing all execution parameters.
+    ///
+    /// # Returns
+    /// * `Ok(Output)` - The exit status and the captured stdout and stderr.
+    /// * `Err` - In the same cases as [`Self::run`].
+    pub fn run_captured(config: SandBoxConfig) -> Result<Output, Box<dyn Error>> {
+        Self::run_with_stdio(config, true)
+    }
+
+    /// Internal: shared body of [`Self::run`] and [`Self::run_captured`].
+    ///
+    /// # Arguments
+    /// * `config` - A `SandBoxConfig` containing all execution parameters.
+    /// * `capture` - Pipes stdout/stderr instead of inheriting the terminal.
+    ///
+    /// # Returns
+    /// The process [`Output`]; its buffers are empty when `capture` is false.
+    fn run_with_stdio(mut config: SandBoxConfig, capture: bool) -> Result<Output, Box<dyn Error>> {
+        config.validate()?;
+
+        if config.dry_run {
+            let line = Self::command_string(&config)?;
+            let stdout = match capture {
+                true => format!("{line}\n").into_bytes(),
+                false => {
+                    println!("{line}");
+                    Vec::new()
+                }
+            };
+            return Ok(Output {
+                status: ExitStatus::from_raw(0),
+                stdout,
+                stderr: Vec::new(),
+            });
+        }
+
+        let base_path = Self::normalize_path(&config.rootfs);
         config.rootfs = base_path.join("rootfs");
 
         if !config.rootfs.exists() {
             return Err(Box::new(RootfsNotFoundError(config.rootfs)));
         }
 
+        let shell = Self::shell(&config);
+        let executable = resolve_in_rootfs(&config.rootfs, Path::new(shell))
+            .and_then(|p| fs::metadata(p).ok())
+            .is_some_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0);
+        if config.run_argv.is_none() && !executable {
+            let expected = config.rootfs.join(shell.trim_start_matches('/'));
+            return Err(Box::new(ShellNotFoundError(expected)));
+        }
+
+        if let Some(workdir) = &config.workdir
+            && !Self::guest_dir_exists(&config, workdir)
+        {
+            return Err(format!(
+                "Working directory {} does not exist in the guest",
+                workdir.display()
+            )
+            .into());
+        }
+
         let overlay_handle: Option<OverlayFS>;
         let effective_rootfs: PathBuf;
 
@@ -169,7 +738,8 @@ impl SandBox {
             overlay_handle = None;
         }
 
-        let run_result = Self::exec_sandbox(&config, &effective_rootfs);
+        let hostname_dir = Self::next_hostname_dir();
+        let run_result = Self::exec_sandbox(&config, &effective_rootfs, capture, &hostname_dir);
 
         if let Some(mut overlay) = overlay_handle {
             overlay.umount();
@@ -179,6 +749,49 @@ impl SandBox {
         run_result
     }
 
+    /// Returns the exact command [`Self::run`] would execute, without running it.
+    ///
+    /// No files are touched: overlay directories, hostname files and the mtab
+    /// fix are only prepared by `run`. Hostname files are shown in the numbered
+    /// directory the next run of this process will use. With `use_overlay`, the
+    /// FUSE mount point is only known after mounting, so the lower rootfs path
+    /// is shown instead.
+    ///
+    /// # Arguments
+    /// * `config` - The sandbox configuration to preview.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<String>)` - The tool target followed by every argument.
+    /// * `Err` - If the configuration is invalid (see [`SandBoxConfig::validate`]).
+    pub fn command_argv(config: &SandBoxConfig) -> Result<Vec<String>, Box<dyn Error>> {
+        config.validate()?;
+
+        let rootfs = Self::normalize_path(&config.rootfs).join("rootfs");
+        let file_binds = match config.hostname {
+            Some(_) => Self::hostname_file_binds(&Self::hostname_dir(
+                NEXT_HOSTNAME_DIR.load(Ordering::Relaxed),
+            )),
+            None => Vec::new(),
+        };
+
+        let mut argv = vec![config.tool_target.to_string_lossy().into_owned()];
+        argv.extend(Self::build_command(config, &rootfs, &file_binds)?);
+        Ok(argv)
+    }
+
+    /// Returns [`Self::command_argv`] as a single shell-quoted command line,
+    /// ready to be pasted into a terminal or a bug report.
+    ///
+    /// # Arguments
+    /// * `config` - The sandbox configuration to preview.
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The quoted command line.
+    /// * `Err` - If the configuration is invalid (see [`SandBoxConfig::validate`]).
+    pub fn command_string(config: &SandBoxConfig) -> Result<String, Box<dyn Error>> {
+        Ok(shell_join(Self::command_argv(config)?))
+    }
+
     /// Internal: builds and spawns the actual sandbox process.
     ///
     /// Separated from [`Self::run`] so the overlay teardown always executes
@@ -189,244 +802,1090 @@ impl SandBox {
     ///   mounts, user identity flags, and the command to run inside the container.
     /// * `rootfs` - The effective root directory to pass to the sandbox tool. When
     ///   overlay is active, this is the overlay mount point, not the original rootfs.
+    /// * `capture` - Pipes stdout/stderr instead of inheriting the terminal.
+    /// * `hostname_dir` - Directory of this run's hostname files, from
+    ///   [`Self::next_hostname_dir`]; removed on return, even on failure.
     ///
     /// # Returns
-    /// * `Ok(())` - If the sandbox process spawns and exits without error.
+    /// * `Ok(Output)` - The exit status and, when capturing, the guest output.
     /// * `Err` - If the tool command is unrecognized or the process fails to start.
-    fn exec_sandbox(config: &SandBoxConfig, rootfs: &Path) -> Result<(), Box<dyn Error>> {
-        let (uid, euid) = unsafe { (getuid(), geteuid()) };
+    fn exec_sandbox(
+        config: &SandBoxConfig,
+        rootfs: &Path,
+        capture: bool,
+        hostname_dir: &Path,
+    ) -> Result<Output, Box<dyn Error>> {
+        let rootfs = Self::normalize_path(rootfs);
+        let rootfs_str: &str = &rootfs.to_string_lossy();
+
+        if config.copy_net_files {
+            Self::copy_net_files(&rootfs);
+        }
+
+        if let Some(overlay) = &config.overlay {
+            Self::prepare_overlay_dirs(overlay)?;
+        }
+
+        let extra = Self::extra_binds(config);
+        log_debug!(
+            "Bound {} optional host paths, skipped {}: {:?}",
+            extra.applied.len(),
+            extra.skipped.len(),
+            extra.skipped
+        );
+
+        let _hostname_dir = config.hostname.is_some().then(|| HostnameDir(hostname_dir));
+        let file_binds = match &config.hostname {
+            Some(name) => Self::write_hostname_files(hostname_dir, name)?,
+            None => Vec::new(),
+        };
+
+        if config.fix_mtab && !config.secure_rootfs {
+            let target = config
+                .mtab_target
+                .as_deref()
+                .unwrap_or(Path::new(MTAB_TARGET));
+            Self::fix_mtab_symlink(rootfs_str, target).map_err(|e| {
+                format!(
+                    "Failed to fix the mtab symlink in {rootfs_str}: {e}; \
+                     set fix_mtab to false to leave the rootfs untouched"
+                )
+            })?;
+        }
+
+        let full_args = Self::build_command(config, &rootfs, &file_binds)?;
+
+        log_debug!("{} {}", config.tool_target.display(), full_args.join(" "));
+
+        let mut command = Command::new(&config.tool_target);
+        command.args(&full_args);
+
+        // Kept open until the tool has exited, since the child inherits it.
+        let seccomp =
+            match (&config.seccomp, config.rootfs_tool.as_str()) {
+                (Some(path), USE_BWRAP) => Some(File::open(path).map_err(|e| {
+                    format!("Failed to open seccomp program {}: {e}", path.display())
+                })?),
+                (Some(path), _) => {
+                    log_warn!("PRoot has no seccomp support, ignoring {}", path.display());
+                    None
+                }
+                (None, _) => None,
+            };
+        let cgroup = match (config.memory_limit, config.cpu_quota) {
+            (None, None) => None,
+            (memory, cpu) => Some(Cgroup::create(memory, cpu)?),
+        };
+        if let Some(fd) = cgroup.as_ref().map(Cgroup::procs_fd) {
+            unsafe {
+                command.pre_exec(move || {
+                    if write(fd, b"0".as_ptr(), 1) < 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        if let Some(fd) = seccomp.as_ref().map(AsRawFd::as_raw_fd) {
+            // Only async-signal-safe calls run between fork and exec; `dup2`
+            // clears close-on-exec on a new descriptor, `fcntl` covers the case
+            // where the file already sits on SECCOMP_FD.
+            unsafe {
+                command.pre_exec(move || {
+                    if dup2(fd, SECCOMP_FD) < 0 || fcntl(SECCOMP_FD, F_SETFD, 0) < 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+        match capture {
+            true => command
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped()),
+            false => command
+                .stdin(Stdio::inherit())
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit()),
+        };
+        let output = Self::wait_tool(&mut command, config.timeout)?;
+        if !output.status.success()
+            && config.run_argv.is_none()
+            && let Some(hint) = diagnose_rootfs_shell(&rootfs, Self::shell(config))
+        {
+            log_warn!("The sandbox failed to start the guest shell: {hint}");
+        }
+
+        Ok(output)
+    }
 
+    /// Assembles the tool arguments for a configuration, without side effects.
+    ///
+    /// Shared by [`Self::run`] and [`Self::command_argv`], so the preview
+    /// always matches what is executed.
+    ///
+    /// # Arguments
+    /// * `config` - The sandbox configuration.
+    /// * `rootfs` - The effective root directory given to the tool.
+    /// * `file_binds` - Generated host files and the guest path each one replaces.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<String>)` - The tool arguments, without the tool itself.
+    /// * `Err` - If the tool command is unrecognized.
+    fn build_command(
+        config: &SandBoxConfig,
+        rootfs: &Path,
+        file_binds: &[(PathBuf, &'static str)],
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let (uid, euid) = (host_uid(), host_euid());
         let tool_cmd = &config.rootfs_tool;
         let rootfs_str: &str = &rootfs.to_string_lossy();
 
+        let args_bind = match config.check_bind_sources {
+            true => Self::drop_missing_binds(tool_cmd, &config.args_bind),
+            false => config.args_bind.clone(),
+        };
+
+        let binds: Vec<BindMount> = Self::integration_binds(config)
+            .into_iter()
+            .chain(config.binds.iter().cloned())
+            .collect();
+        let bind_args = Self::bind_options(tool_cmd, &binds, config.check_bind_sources);
+
         let args = match tool_cmd.as_ref() {
             USE_PROOT => Self::build_proot_options(
                 rootfs_str,
-                &config.args_bind,
+                &args_bind,
+                &bind_args,
                 config.ignore_extra_bind,
                 config.secure_rootfs,
+                config.copy_net_files,
+                file_binds,
+                config.pseudo_fs,
             ),
             USE_BWRAP => Self::build_bwrap_options(
                 rootfs_str,
-                &config.args_bind,
+                &args_bind,
+                &bind_args,
                 config.ignore_extra_bind,
                 config.secure_rootfs,
+                config.copy_net_files,
+                file_binds,
+                config.overlay.as_ref(),
+                config.pseudo_fs,
+                config.share_net,
             ),
             other => return Err(format!("Unsupported rootfs command: {}", other).into()),
         };
 
         let new_cmd = &config.run_cmd;
-        let mut full_args: Vec<&str> = args.split_whitespace().collect();
+        let mut full_args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        let ps1 = match (&config.prompt, config.use_root) {
+            (Some(prompt), _) => prompt.as_str(),
+            (None, true) => "# ",
+            (None, false) => "$ ",
+        };
 
-        let user = match config.use_root {
-            true => "PS1=# |USER=root|LOGNAME=root|UID=0|EUID=0".to_string(),
-            false => format!("PS1=$ |UID={uid}|EUID={euid}"),
+        let user = match (&config.user, config.use_root) {
+            (Some(user), _) => Some(user.as_str()),
+            (None, true) => Some("root"),
+            (None, false) => None,
         };
 
+        let mut env_vars = vec![format!("PS1={ps1}")];
+        if let Some(user) = user {
+            env_vars.extend([format!("USER={user}"), format!("LOGNAME={user}")]);
+        }
+        match config.use_root {
+            true => env_vars.extend(["UID=0", "EUID=0"].map(String::from)),
+            false => env_vars.extend([format!("UID={uid}"), format!("EUID={euid}")]),
+        }
+
+        // PRoot reads its options up to the first non-option argument, so
+        // `-0` only has to precede `env`, like every other flag added here.
         if tool_cmd == USE_PROOT && config.use_root {
             full_args.push("-0");
         }
 
         if tool_cmd == USE_BWRAP && config.use_root {
-            full_args.extend([
-                "--uid", "0", "--gid", "0", "--setenv", "USER", "root", "--setenv", "LOGNAME",
-                "root",
-            ]);
+            full_args.extend(["--uid", "0", "--gid", "0"]);
+        }
+
+        if let Some(user) = user
+            && tool_cmd == USE_BWRAP
+        {
+            full_args.extend(["--setenv", "USER", user, "--setenv", "LOGNAME", user]);
+        }
+
+        if let Some(name) = &config.hostname
+            && tool_cmd == USE_BWRAP
+        {
+            full_args.extend(["--unshare-uts", "--hostname", name]);
+        }
+
+        let workdir = config.workdir.as_ref().map(|w| w.to_string_lossy());
+        match (tool_cmd.as_str(), &workdir) {
+            (USE_BWRAP, Some(dir)) => full_args.extend(["--chdir", dir]),
+            (USE_PROOT, Some(dir)) => full_args.extend(["-w", dir]),
+            _ => {}
         }
 
+        let device_args = Self::device_options(tool_cmd, &config.devices);
+        full_args.extend(device_args.iter().map(String::as_str));
+
+        let env: Vec<(String, String)> = Self::integration_env(config)
+            .into_iter()
+            .chain(config.env.iter().cloned())
+            .collect();
+        if tool_cmd == USE_BWRAP {
+            for (key, value) in &env {
+                full_args.extend(["--setenv", key, value]);
+            }
+        }
+
+        let seccomp_fd = SECCOMP_FD.to_string();
+        if tool_cmd == USE_BWRAP && config.seccomp.is_some() {
+            full_args.extend(["--seccomp", &seccomp_fd]);
+        }
+
+        full_args.extend(config.raw_tool_args.iter().map(String::as_str));
+
+        // Remounting last lets bwrap create the mount points of the earlier
+        // binds; a plain `--ro-bind` root would fail on a missing home dir.
+        if tool_cmd == USE_BWRAP && config.readonly_rootfs {
+            full_args.extend(["--remount-ro", "/"]);
+        }
+
+        let shell = Self::shell(config);
+        let shell_var = format!("SHELL={shell}");
+        let user_env: Vec<String> = env.iter().map(|(k, v)| format!("{k}={v}")).collect();
+
         full_args.push("env");
-        full_args.extend_from_slice(&user.split('|').collect::<Vec<_>>());
+        full_args.extend(env_vars.iter().map(String::as_str));
         full_args.extend([
-            "SHELL=/bin/sh",
+            &shell_var,
             "PATH=/bin:/sbin:/usr/bin:/usr/sbin:/usr/libexec",
-            "/bin/sh",
         ]);
+        full_args.extend(user_env.iter().map(String::as_str));
 
-        if !new_cmd.is_empty() {
-            full_args.push("-c");
-            full_args.push(new_cmd);
+        match &config.run_argv {
+            Some(argv) => full_args.extend(argv.iter().map(String::as_str)),
+            None => {
+                full_args.push(shell);
+                if !new_cmd.is_empty() {
+                    full_args.extend(["-c", new_cmd]);
+                }
+            }
         }
 
-        Command::new(&config.tool_target)
-            .args(&full_args)
-            .stdin(Stdio::inherit())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .status()?;
+        Ok(full_args.into_iter().map(String::from).collect())
+    }
 
-        Ok(())
+    /// Runs the tool and waits for it, enforcing the optional timeout.
+    ///
+    /// With a timeout, the tool is started as the leader of a new process
+    /// group and a watchdog thread signals the whole group on expiry, which
+    /// also reaches the guest processes that outlived their parent. Any of
+    /// them that became children of this process are reaped afterwards.
+    ///
+    /// # Arguments
+    /// * `command` - The fully configured tool command, with its stdio set.
+    /// * `timeout` - Maximum run time, or `None` to wait indefinitely.
+    ///
+    /// # Returns
+    /// * `Ok(Output)` - The exit status and the output of the piped streams.
+    /// * `Err` - If the process fails to start, or a [`TimedOutError`].
+    fn wait_tool(
+        command: &mut Command,
+        timeout: Option<Duration>,
+    ) -> Result<Output, Box<dyn Error>> {
+        let Some(limit) = timeout else {
+            return Ok(command.output()?);
+        };
+
+        let child = command.process_group(0).spawn()?;
+        let pgid = child.id() as i32;
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+
+        let watchdog = thread::spawn(move || {
+            if done_rx.recv_timeout(limit) != Err(RecvTimeoutError::Timeout) {
+                return false;
+            }
+            log_warn!("Sandbox exceeded its {limit:?} timeout, terminating it");
+            unsafe { kill(-pgid, SIGTERM) };
+            // The leader may exit first; whatever is left in the group is killed.
+            let _ = done_rx.recv_timeout(KILL_GRACE);
+            unsafe { kill(-pgid, SIGKILL) };
+            true
+        });
+
+        let output = child.wait_with_output();
+        let _ = done_tx.send(());
+
+        if watchdog.join().unwrap_or(false) {
+            // Guest processes orphaned by the tool are reparented to this
+            // process when it is PID 1 or a subreaper; reap them so the killed
+            // group leaves no zombies behind. Returns -1 once none are left.
+            let mut status = 0;
+            while unsafe { waitpid(-pgid, &mut status, 0) } > 0 {}
+            return Err(Box::new(TimedOutError(limit)));
+        }
+        Ok(output?)
     }
 
-    /// Generates the argument string specifically for PRoot.
+    /// Generates the arguments specifically for PRoot.
     ///
     /// # Arguments
     /// * `rootfs` - String slice of the guest root directory path.
-    /// * `rootfs_args` - Extra user-defined bind arguments.
+    /// * `rootfs_args` - Extra user-defined raw arguments, split on whitespace.
+    /// * `bind_args` - Arguments of the structured bind mounts, one element each.
     /// * `no_extra_binds` - Boolean to toggle mounting of host fonts/themes.
     /// * `secure_rootfs` - Enables strict isolation, skipping host system path mapping.
+    /// * `copy_net_files` - Skips binding the host network files, which were copied instead.
+    /// * `file_binds` - Generated host files and the guest path each one replaces.
+    /// * `pseudo_fs` - How `/dev`, `/proc`, `/sys`, `/tmp` and `/run` are provided.
     ///
     /// # Returns
-    /// A `String` containing the formatted CLI arguments for PRoot.
+    /// The PRoot CLI arguments, one `argv` element each.
+    #[allow(clippy::too_many_arguments)]
     fn build_proot_options(
         rootfs: &str,
         rootfs_args: &str,
+        bind_args: &[String],
         no_extra_binds: bool,
         secure_rootfs: bool,
-    ) -> String {
-        let mut proot_options = match secure_rootfs {
-            true => format!("-S {rootfs} {rootfs_args}"),
-            false => format!("-R {rootfs} --bind=/media --bind=/mnt {rootfs_args}"),
+        copy_net_files: bool,
+        file_binds: &[(PathBuf, &'static str)],
+        pseudo_fs: PseudoFs,
+    ) -> Vec<String> {
+        let mut proot_options: Vec<String> = match secure_rootfs {
+            true => vec!["-S".into(), rootfs.into()],
+            false => ["-R", rootfs, "--bind=/media", "--bind=/mnt"]
+                .map(String::from)
+                .into(),
         };
+        proot_options.extend(rootfs_args.split_whitespace().map(String::from));
+        proot_options.extend_from_slice(bind_args);
 
-        if !secure_rootfs && !no_extra_binds {
-            let extra_paths = [
-                "/etc/asound.conf",
-                "/etc/fonts",
-                "/usr/share/font-config",
-                "/usr/share/fontconfig",
-                "/usr/share/fonts",
-                "/usr/share/themes",
-            ];
-
-            for path in extra_paths {
-                if Path::new(path).exists() {
-                    proot_options.push_str(" --bind=");
-                    proot_options.push_str(path);
-                }
+        // `-S` keeps the host `/tmp` and `/run` out of the guest.
+        let pseudo_paths: &[&str] = match (pseudo_fs, secure_rootfs) {
+            (PseudoFs::Host, false) => &["/dev", "/proc", "/sys", "/tmp", "/run"],
+            _ => &["/dev", "/proc", "/sys"],
+        };
+        for path in pseudo_paths.iter().filter(|p| Path::new(p).exists()) {
+            proot_options.push(format!("--bind={path}"));
+        }
+
+        // PRoot has no "bind if present" flag, so missing host files are skipped here.
+        for path in Self::shared_etc_files(secure_rootfs, copy_net_files, file_binds) {
+            if Path::new(path).exists() {
+                proot_options.push(format!("--bind={path}"));
             }
+        }
 
-            if let Ok(entries) = fs::read_dir("/usr/share/icons") {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    let cursor_path = path.join("cursors");
+        for (source, dest) in file_binds {
+            proot_options.push(format!("--bind={}:{dest}", source.display()));
+        }
 
-                    if cursor_path.is_dir() {
-                        if let Some(p_str) = cursor_path.to_str() {
-                            proot_options.push_str(" --bind=");
-                            proot_options.push_str(p_str);
-                        }
-                    }
-                }
+        if !secure_rootfs && !no_extra_binds {
+            for path in Self::optional_binds(no_extra_binds, secure_rootfs).applied {
+                proot_options.push(format!("--bind={path}"));
             }
         }
 
         proot_options
     }
 
-    /// Generates the argument string specifically for Bubblewrap.
+    /// Generates the arguments specifically for Bubblewrap.
     ///
     /// # Arguments
     /// * `rootfs` - String slice of the guest root directory path.
-    /// * `rootfs_args` - Extra user-defined bind arguments.
+    /// * `rootfs_args` - Extra user-defined raw arguments, split on whitespace.
+    /// * `bind_args` - Arguments of the structured bind mounts, one element each.
     /// * `ignore_extra_binds` - Boolean to toggle mounting of host fonts/themes.
     /// * `secure_rootfs` - Skips host file sharing for maximum sandbox isolation.
+    /// * `copy_net_files` - Skips binding the host network files, which were copied instead.
+    /// * `file_binds` - Generated host files and the guest path each one replaces.
+    /// * `overlay` - Mounts the rootfs as an overlay lower layer instead of binding it.
+    /// * `pseudo_fs` - How `/dev`, `/proc`, `/sys`, `/tmp` and `/run` are provided.
+    /// * `share_net` - Keeps the host network; otherwise it gets a private namespace.
     ///
     /// # Returns
-    /// A `String` containing the formatted CLI arguments for Bubblewrap.
+    /// The Bubblewrap CLI arguments, one `argv` element each.
+    #[allow(clippy::too_many_arguments)]
     fn build_bwrap_options(
         rootfs: &str,
         rootfs_args: &str,
+        bind_args: &[String],
         ignore_extra_binds: bool,
         secure_rootfs: bool,
-    ) -> String {
-        let mut bwrap_options = format!(
-            "--unshare-user \
-             --share-net \
-             --bind {rootfs} / \
-             --die-with-parent \
-             --ro-bind-try /etc/host.conf /etc/host.conf \
-             --ro-bind-try /etc/hosts /etc/hosts \
-             --ro-bind-try /etc/nsswitch.conf /etc/nsswitch.conf \
-             --ro-bind-try /etc/resolv.conf /etc/resolv.conf \
-             --dev-bind /dev /dev \
-             --ro-bind /sys /sys \
-             --bind-try /proc /proc \
-             --bind-try /tmp /tmp \
-             --bind-try /run /run \
-             --bind {home} {home} \
-             {rootfs_args} \
-             --setenv PATH \"/bin:/sbin:/usr/bin:/usr/sbin:/usr/libexec\"",
-            home = safe_home().to_string_lossy(),
+        copy_net_files: bool,
+        file_binds: &[(PathBuf, &'static str)],
+        overlay: Option<&OverlayConfig>,
+        pseudo_fs: PseudoFs,
+        share_net: bool,
+    ) -> Vec<String> {
+        let net = match share_net {
+            true => "--share-net",
+            false => "--unshare-net",
+        };
+        let mut bwrap_options: Vec<String> = ["--unshare-user", net].map(String::from).into();
+
+        match overlay {
+            Some(o) => bwrap_options.extend([
+                "--overlay-src".into(),
+                rootfs.into(),
+                "--overlay".into(),
+                o.upper.to_string_lossy().into_owned(),
+                o.work.to_string_lossy().into_owned(),
+                "/".into(),
+            ]),
+            None => bwrap_options.extend(["--bind".into(), rootfs.into(), "/".into()]),
+        }
+        bwrap_options.push("--die-with-parent".into());
+
+        // Fixed paths without spaces, so splitting the literal is safe.
+        let pseudo_mounts = match pseudo_fs {
+            PseudoFs::Host => {
+                "--dev-bind /dev /dev \
+                 --ro-bind /sys /sys \
+                 --bind-try /proc /proc \
+                 --bind-try /tmp /tmp \
+                 --bind-try /run /run"
+            }
+            PseudoFs::Private => {
+                "--dev /dev \
+                 --ro-bind /sys /sys \
+                 --proc /proc \
+                 --tmpfs /tmp \
+                 --tmpfs /run"
+            }
+        };
+        bwrap_options.extend(pseudo_mounts.split_whitespace().map(String::from));
+
+        let home = safe_home().to_string_lossy().into_owned();
+        bwrap_options.extend(["--bind".into(), home.clone(), home]);
+        bwrap_options.extend(rootfs_args.split_whitespace().map(String::from));
+        bwrap_options.extend_from_slice(bind_args);
+        bwrap_options.extend(
+            [
+                "--setenv",
+                "PATH",
+                "/bin:/sbin:/usr/bin:/usr/sbin:/usr/libexec",
+            ]
+            .map(String::from),
         );
 
+        for path in Self::shared_etc_files(secure_rootfs, copy_net_files, file_binds) {
+            bwrap_options.extend(["--ro-bind-try", path, path].map(String::from));
+        }
+
+        for (source, dest) in file_binds {
+            bwrap_options.extend([
+                "--ro-bind".into(),
+                source.to_string_lossy().into_owned(),
+                dest.to_string(),
+            ]);
+        }
+
         if !secure_rootfs {
-            bwrap_options.push_str(
-                " --ro-bind-try /etc/hosts.equiv /etc/hosts.equiv \
-                --ro-bind-try /etc/netgroup /etc/netgroup \
-                --ro-bind-try /etc/networks /etc/networks \
-                --ro-bind-try /etc/localtime /etc/localtime \
-                --ro-bind-try /etc/passwd /etc/passwd \
-                --ro-bind-try /etc/group /etc/group \
-                --ro-bind /var/run/dbus/system_bus_socket /var/run/dbus/system_bus_socket \
-                --bind /media /media \
-                --bind /mnt /mnt",
+            bwrap_options.extend(
+                "--ro-bind /var/run/dbus/system_bus_socket /var/run/dbus/system_bus_socket \
+                 --bind /media /media \
+                 --bind /mnt /mnt"
+                    .split_whitespace()
+                    .map(String::from),
             );
 
-            Self::fix_mtab_symlink(rootfs);
-
-            if !ignore_extra_binds {
-                let extra_paths = [
-                    "/etc/asound.conf",
-                    "/etc/fonts",
-                    "/usr/share/font-config",
-                    "/usr/share/fontconfig",
-                    "/usr/share/fonts",
-                    "/usr/share/themes",
-                ];
-
-                for path in extra_paths {
-                    if Path::new(path).exists() {
-                        bwrap_options.push_str(" --ro-bind ");
-                        bwrap_options.push_str(path);
-                        bwrap_options.push_str(" ");
-                        bwrap_options.push_str(path);
+            for path in Self::optional_binds(ignore_extra_binds, secure_rootfs).applied {
+                bwrap_options.extend(["--ro-bind".into(), path.clone(), path]);
+            }
+        }
+        bwrap_options
+    }
+
+    /// Reports which optional host paths a configuration binds into the guest.
+    ///
+    /// Uses the same existence checks as the argument builders, so the result
+    /// matches what [`Self::run`] mounts for either tool.
+    ///
+    /// # Arguments
+    /// * `config` - The sandbox configuration to inspect.
+    ///
+    /// # Returns
+    /// An [`ExtraBindReport`] listing the applied and skipped paths.
+    pub fn extra_binds(config: &SandBoxConfig) -> ExtraBindReport {
+        Self::optional_binds(config.ignore_extra_bind, config.secure_rootfs)
+    }
+
+    /// Probes [`EXTRA_PATHS`] and the icon theme cursor directories.
+    ///
+    /// # Arguments
+    /// * `ignore_extra_binds` - Disables every optional bind.
+    /// * `secure_rootfs` - Disables every optional bind as well.
+    ///
+    /// # Returns
+    /// An [`ExtraBindReport`]; cursor directories are only listed when applied.
+    fn optional_binds(ignore_extra_binds: bool, secure_rootfs: bool) -> ExtraBindReport {
+        let mut report = ExtraBindReport::default();
+
+        if ignore_extra_binds || secure_rootfs {
+            report.skipped = EXTRA_PATHS.map(String::from).to_vec();
+            return report;
+        }
+
+        for path in EXTRA_PATHS {
+            match Path::new(path).exists() {
+                true => report.applied.push(path.to_string()),
+                false => report.skipped.push(path.to_string()),
+            }
+        }
+
+        report.applied.extend(Self::cursor_dirs().iter().cloned());
+        report
+    }
+
+    /// Creates the overlay upper and work directories and checks they are writable.
+    ///
+    /// # Arguments
+    /// * `overlay` - The configured overlay directories.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If both directories exist and accept new files.
+    /// * `Err` - If a directory cannot be created or is not writable.
+    fn prepare_overlay_dirs(overlay: &OverlayConfig) -> Result<(), Box<dyn Error>> {
+        for dir in [&overlay.upper, &overlay.work] {
+            fs::create_dir_all(dir)?;
+
+            let probe = dir.join(format!(".write-test.{}", std::process::id()));
+            fs::write(&probe, b"")
+                .map_err(|e| format!("Overlay directory {} is not writable: {e}", dir.display()))?;
+            let _ = fs::remove_file(&probe);
+        }
+        Ok(())
+    }
+
+    /// Removes the user binds whose host source does not exist.
+    ///
+    /// Recognizes the PRoot forms `--bind=SRC[:DST]`, `-b SRC[:DST]` and
+    /// `--bind SRC[:DST]`, and the Bubblewrap `--bind`, `--ro-bind` and
+    /// `--dev-bind` pairs. The `-try` variants and any other argument are kept
+    /// as they are. Every dropped bind is reported with a warning.
+    ///
+    /// # Arguments
+    /// * `tool` - The selected sandbox tool, which decides the bind syntax.
+    /// * `args_bind` - The user-provided bind arguments.
+    ///
+    /// # Returns
+    /// The arguments without the binds that would make the launch fail.
+    fn drop_missing_binds(tool: &str, args_bind: &str) -> String {
+        let mut kept: Vec<&str> = Vec::new();
+        let mut tokens = args_bind.split_whitespace();
+
+        let source_exists = |spec: &str, dropped: &str| {
+            let source = match tool {
+                USE_PROOT => spec.split(':').next().unwrap_or(spec),
+                _ => spec,
+            };
+            let exists = Path::new(source).exists();
+            if !exists {
+                log_warn!("Skipping bind '{dropped}': {source} does not exist on the host");
+            }
+            exists
+        };
+
+        while let Some(token) = tokens.next() {
+            match (tool, token) {
+                (USE_PROOT, t) if t.starts_with("--bind=") => {
+                    if source_exists(&t["--bind=".len()..], t) {
+                        kept.push(t);
                     }
                 }
-
-                if let Ok(entries) = fs::read_dir("/usr/share/icons") {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        let cursor_path = path.join("cursors");
-                        if cursor_path.is_dir() {
-                            if let Some(p_str) = cursor_path.to_str() {
-                                bwrap_options.push_str(" --ro-bind ");
-                                bwrap_options.push_str(p_str);
-                                bwrap_options.push_str(" ");
-                                bwrap_options.push_str(p_str);
+                (USE_PROOT, "-b" | "--bind") => {
+                    let Some(spec) = tokens.next() else {
+                        kept.push(token);
+                        continue;
+                    };
+                    if source_exists(spec, spec) {
+                        kept.extend([token, spec]);
+                    }
+                }
+                (USE_BWRAP, "--bind" | "--ro-bind" | "--dev-bind") => {
+                    match (tokens.next(), tokens.next()) {
+                        (Some(src), Some(dest)) => {
+                            if source_exists(src, &format!("{token} {src} {dest}")) {
+                                kept.extend([token, src, dest]);
                             }
                         }
+                        (src, _) => kept.extend([Some(token), src].into_iter().flatten()),
                     }
                 }
+                _ => kept.push(token),
             }
         }
-        bwrap_options
+
+        kept.join(" ")
     }
 
-    /// Fixes or creates the `/etc/mtab` symlink inside the RootFS.
+    /// Generates the arguments for the structured bind mounts.
     ///
     /// # Arguments
-    /// * `rootfs` - String slice of the guest root directory path.
-    fn fix_mtab_symlink(rootfs: &str) {
-        let mtab_path = Path::new(rootfs).join("etc").join("mtab");
-        let target = "/proc/self/mounts";
-
-        if let Ok(md) = fs::symlink_metadata(&mtab_path) {
-            if md.is_symlink() {
-                if let Ok(existing_target) = fs::read_link(&mtab_path) {
-                    if existing_target.to_string_lossy() == target {
-                        return;
-                    }
-                }
+    /// * `tool` - The selected sandbox tool, which decides the bind syntax.
+    /// * `binds` - The mounts from `SandBoxConfig::binds`.
+    /// * `check_sources` - Drops mounts whose source is missing, with a warning.
+    ///
+    /// # Returns
+    /// The tool arguments, one `argv` element per entry.
+    fn bind_options(tool: &str, binds: &[BindMount], check_sources: bool) -> Vec<String> {
+        let mut args = Vec::new();
+
+        for mount in binds {
+            let missing = !mount.source.exists();
+            if missing && mount.optional {
+                continue;
+            }
+            if missing && check_sources {
+                log_warn!(
+                    "Skipping bind {} -> {}: source does not exist on the host",
+                    mount.source.display(),
+                    mount.dest.display()
+                );
+                continue;
             }
+            args.extend(mount.to_args(tool));
+        }
+
+        args
+    }
+
+    /// Returns the optional mounts of the host integrations enabled in a
+    /// configuration. They come before `binds`, so a user mount on the same
+    /// guest path wins.
+    ///
+    /// # Arguments
+    /// * `config` - The sandbox configuration to inspect.
+    ///
+    /// # Returns
+    /// The mounts, all optional so missing host paths are skipped silently.
+    fn integration_binds(config: &SandBoxConfig) -> Vec<BindMount> {
+        let mut binds = Vec::new();
+
+        if config.bind_gpu {
+            binds.push(BindMount {
+                source: GPU_DIR.into(),
+                dest: GPU_DIR.into(),
+                optional: true,
+                device: true,
+                ..Default::default()
+            });
         }
 
-        let _ = fs::remove_file(&mtab_path);
-        if mtab_path.is_dir() {
-            let _ = fs::remove_dir_all(&mtab_path);
+        binds.extend(
+            Self::audio_sockets(config)
+                .into_iter()
+                .map(|socket| BindMount {
+                    source: socket.clone(),
+                    dest: socket,
+                    optional: true,
+                    ..Default::default()
+                }),
+        );
+
+        if Self::x11_display(config).is_some() {
+            binds.push(BindMount {
+                source: X11_SOCKET_DIR.into(),
+                dest: X11_SOCKET_DIR.into(),
+                read_only: true,
+                optional: true,
+                ..Default::default()
+            });
         }
 
-        if let Err(e) = unix::fs::symlink(target, &mtab_path) {
-            eprintln!("\x1b[1;33mWarning\x1b[0m: Failed to fix mtab symlink: {e}");
+        if let Some((socket, _)) = Self::wayland_display(config) {
+            binds.push(BindMount {
+                source: socket.clone(),
+                dest: socket,
+                optional: true,
+                ..Default::default()
+            });
         }
+
+        binds
+    }
+
+    /// Returns the environment of the host integrations enabled in a
+    /// configuration. It comes before `env`, so a user value wins.
+    ///
+    /// # Arguments
+    /// * `config` - The sandbox configuration to inspect.
+    ///
+    /// # Returns
+    /// The variables, only for the sockets that exist on the host.
+    fn integration_env(config: &SandBoxConfig) -> Vec<(String, String)> {
+        let mut env = Vec::new();
+        let sockets = Self::audio_sockets(config);
+        let wayland = Self::wayland_display(config);
+
+        if let Some(pulse) = sockets.iter().find(|s| s.ends_with(AUDIO_SOCKETS[0])) {
+            env.push(("PULSE_SERVER".into(), format!("unix:{}", pulse.display())));
+        }
+        if let Some(display) = Self::x11_display(config) {
+            env.push(("DISPLAY".into(), display));
+        }
+        if let Some((_, name)) = &wayland {
+            env.push(("WAYLAND_DISPLAY".into(), name.clone()));
+        }
+        if let Some(runtime_dir) =
+            Self::runtime_dir().filter(|_| !sockets.is_empty() || wayland.is_some())
+        {
+            env.push((
+                "XDG_RUNTIME_DIR".into(),
+                runtime_dir.to_string_lossy().into(),
+            ));
+        }
+
+        env
+    }
+
+    /// Returns the audio server sockets present on the host, when `bind_audio`
+    /// is set.
+    fn audio_sockets(config: &SandBoxConfig) -> Vec<PathBuf> {
+        match (config.bind_audio, Self::runtime_dir()) {
+            (true, Some(dir)) => AUDIO_SOCKETS
+                .iter()
+                .map(|socket| dir.join(socket))
+                .filter(|socket| socket.exists())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the host `$DISPLAY`, when `bind_display` is set and the X11
+    /// socket directory exists.
+    fn x11_display(config: &SandBoxConfig) -> Option<String> {
+        env::var("DISPLAY")
+            .ok()
+            .filter(|d| config.bind_display && !d.is_empty() && Path::new(X11_SOCKET_DIR).is_dir())
+    }
+
+    /// Returns the host Wayland socket and the `$WAYLAND_DISPLAY` value, when
+    /// `bind_display` is set and the socket exists. A relative name is looked
+    /// up in `$XDG_RUNTIME_DIR`, as the compositor does.
+    fn wayland_display(config: &SandBoxConfig) -> Option<(PathBuf, String)> {
+        if !config.bind_display {
+            return None;
+        }
+
+        let name = env::var("WAYLAND_DISPLAY").ok().filter(|n| !n.is_empty())?;
+        let socket = match Path::new(&name).is_absolute() {
+            true => PathBuf::from(&name),
+            false => Self::runtime_dir()?.join(&name),
+        };
+        socket.exists().then_some((socket, name))
+    }
+
+    /// Returns the host `$XDG_RUNTIME_DIR`, if set to an absolute path.
+    fn runtime_dir() -> Option<PathBuf> {
+        env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+    }
+
+    /// Returns the guest shell of a configuration, `/bin/sh` by default.
+    fn shell(config: &SandBoxConfig) -> &str {
+        config.shell.as_deref().unwrap_or(DEFAULT_SHELL)
+    }
+
+    /// Checks that a directory will be visible in the guest.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration, with `rootfs` already pointing at the
+    ///   rootfs directory itself.
+    /// * `dir` - The absolute guest path.
+    ///
+    /// # Returns
+    /// `true` if the path is a directory in the rootfs (following symlinks
+    /// inside it), in the bound home directory, or under a bind destination.
+    fn guest_dir_exists(config: &SandBoxConfig, dir: &Path) -> bool {
+        if resolve_in_rootfs(&config.rootfs, dir).is_some_and(|p| p.is_dir()) {
+            return true;
+        }
+
+        let home = safe_home();
+        let mounts = config
+            .binds
+            .iter()
+            .map(|b| (b.dest.as_path(), b.source.as_path()))
+            .chain([(home.as_path(), home.as_path())]);
+
+        for (dest, source) in mounts {
+            if let Ok(rest) = dir.strip_prefix(dest)
+                && source.join(rest).is_dir()
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Generates the bind arguments for the requested device nodes.
+    ///
+    /// # Arguments
+    /// * `tool` - The selected sandbox tool, which decides the bind syntax.
+    /// * `devices` - Device node paths, already validated to be under `/dev`.
+    ///
+    /// # Returns
+    /// The tool arguments; nodes missing on the host are skipped with a warning.
+    fn device_options(tool: &str, devices: &[PathBuf]) -> Vec<String> {
+        let mut args = Vec::new();
+
+        for device in devices {
+            if !device.exists() {
+                log_warn!(
+                    "Skipping device {}: not found on the host",
+                    device.display()
+                );
+                continue;
+            }
+
+            let path = device.to_string_lossy().into_owned();
+            match tool {
+                USE_BWRAP => args.extend(["--dev-bind".to_string(), path.clone(), path]),
+                _ => args.push(format!("--bind={path}")),
+            }
+        }
+
+        args
+    }
+
+    /// Returns the host `/etc` files shared with the guest by every tool.
+    ///
+    /// Both builders use this list so name resolution and user lookups behave
+    /// the same regardless of the backend; only the bind syntax differs.
+    ///
+    /// # Arguments
+    /// * `secure_rootfs` - Restricts the list to the core NSS and network files.
+    /// * `copy_net_files` - Leaves out [`NET_FILES`], which were copied instead.
+    /// * `file_binds` - Generated files; the guest paths they replace are left out.
+    ///
+    /// # Returns
+    /// The absolute host paths to bind at the same location in the guest.
+    fn shared_etc_files(
+        secure_rootfs: bool,
+        copy_net_files: bool,
+        file_binds: &[(PathBuf, &'static str)],
+    ) -> Vec<&'static str> {
+        let mut files = NSS_CORE_FILES.to_vec();
+
+        if !copy_net_files {
+            files.extend(NET_FILES);
+        }
+
+        if !secure_rootfs {
+            files.extend(NSS_EXTRA_FILES);
+        }
+
+        files.retain(|path| file_binds.iter().all(|(_, dest)| dest != path));
+        files
+    }
+
+    /// Writes the `hostname` and `hosts` files for a custom guest hostname.
+    ///
+    /// The hosts file starts from the host's `/etc/hosts` with any `127.0.1.1`
+    /// line replaced, so lookups of the guest's own name resolve locally while
+    /// the remaining entries are kept.
+    ///
+    /// # Arguments
+    /// * `dir` - Directory receiving the generated files; created if missing.
+    /// * `hostname` - The hostname configured for the guest.
+    ///
+    /// # Returns
+    /// * `Ok(Vec)` - Each generated file paired with the guest path it replaces.
+    /// * `Err` - If the directory or the files cannot be written.
+    fn write_hostname_files(
+        dir: &Path,
+        hostname: &str,
+    ) -> Result<Vec<(PathBuf, &'static str)>, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+
+        let mut hosts: String = fs::read_to_string("/etc/hosts")
+            .unwrap_or_else(|_| "127.0.0.1\tlocalhost\n".to_string())
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("127.0.1.1"))
+            .flat_map(|line| [line, "\n"])
+            .collect();
+        hosts.push_str(&format!("127.0.1.1\t{hostname}\n"));
+
+        let file_binds = Self::hostname_file_binds(dir);
+        fs::write(&file_binds[0].0, format!("{hostname}\n"))?;
+        fs::write(&file_binds[1].0, hosts)?;
+
+        Ok(file_binds)
+    }
+
+    /// Reserves the directory for the generated hostname files of one run,
+    /// unique across the runs of this process so concurrent sandboxes never
+    /// share or remove each other's files.
+    fn next_hostname_dir() -> PathBuf {
+        Self::hostname_dir(NEXT_HOSTNAME_DIR.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Returns the directory for the hostname files of the `run`-th run of
+    /// this process.
+    fn hostname_dir(run: u64) -> PathBuf {
+        temp_cache().join(format!("hostname-{}-{run}", std::process::id()))
+    }
+
+    /// Returns the generated hostname files and the guest path each one replaces.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory holding the generated files.
+    ///
+    /// # Returns
+    /// The `/etc/hostname` and `/etc/hosts` replacements, in that order.
+    fn hostname_file_binds(dir: &Path) -> Vec<(PathBuf, &'static str)> {
+        vec![
+            (dir.join("hostname"), "/etc/hostname"),
+            (dir.join("hosts"), "/etc/hosts"),
+        ]
+    }
+
+    /// Copies the host network configuration files into the RootFS `/etc`.
+    ///
+    /// Any existing entry is removed first, so a symlink inside the RootFS
+    /// (e.g. `resolv.conf -> /run/...`) is replaced rather than followed to a
+    /// host path. Missing host files are skipped; failures are only warned about.
+    ///
+    /// # Arguments
+    /// * `rootfs` - The effective root directory of the guest.
+    fn copy_net_files(rootfs: &Path) {
+        for path in NET_FILES {
+            let source = Path::new(path);
+            if !source.exists() {
+                continue;
+            }
+
+            let dest = rootfs.join(path.trim_start_matches('/'));
+            let _ = fs::remove_file(&dest);
+
+            if let Err(e) = fs::copy(source, &dest) {
+                log_warn!("Failed to copy {path} into the rootfs: {e}");
+            }
+        }
+    }
+
+    /// Normalizes a path by rebuilding it from its components.
+    ///
+    /// Strips trailing separators, collapses repeated slashes and drops `.`
+    /// components, so `/tmp/box/` and `/tmp//box` both become `/tmp/box` and
+    /// produce identical tool arguments. Symlinks are left untouched.
+    ///
+    /// # Arguments
+    /// * `path` - The path to normalize.
+    ///
+    /// # Returns
+    /// The normalized `PathBuf`.
+    fn normalize_path(path: &Path) -> PathBuf {
+        path.components().collect()
+    }
+
+    /// Returns the `cursors` directories of the icon themes in `/usr/share/icons`.
+    ///
+    /// The scan runs once per process and is cached, since every launch would
+    /// otherwise stat each theme directory again. Themes are probed in parallel
+    /// and the result is sorted, so the generated bind list is deterministic.
+    ///
+    /// # Returns
+    /// A slice with the absolute path of every existing cursor directory.
+    fn cursor_dirs() -> &'static [String] {
+        CURSOR_DIRS.get_or_init(|| {
+            let themes: Vec<PathBuf> = match fs::read_dir("/usr/share/icons") {
+                Ok(entries) => entries.flatten().map(|e| e.path()).collect(),
+                Err(_) => return Vec::new(),
+            };
+
+            let workers = thread::available_parallelism().map_or(1, |n| n.get());
+            let chunk_size = themes.len().div_ceil(workers).max(1);
+
+            let mut dirs: Vec<String> = thread::scope(|scope| {
+                let handles: Vec<_> = themes
+                    .chunks(chunk_size)
+                    .map(|chunk| {
+                        scope.spawn(move || {
+                            chunk
+                                .iter()
+                                .map(|theme| theme.join("cursors"))
+                                .filter(|cursor_path| cursor_path.is_dir())
+                                .filter_map(|cursor_path| cursor_path.to_str().map(String::from))
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+
+                handles
+                    .into_iter()
+                    .flat_map(|h| h.join().unwrap_or_default())
+                    .collect()
+            });
+
+            dirs.sort();
+            dirs
+        })
+    }
+
+    /// Fixes or creates the `/etc/mtab` symlink inside the RootFS.
+    ///
+    /// The new symlink is created under a temporary name and atomically renamed
+    /// over `etc/mtab`, so there is no window where the path is missing or
+    /// half-replaced. A directory at `etc/mtab` cannot be replaced by `rename`
+    /// and is removed explicitly first. Concurrent runs on the same rootfs each
+    /// use their own temporary name, and a run that fails after another one
+    /// already fixed the symlink still succeeds.
+    ///
+    /// # Arguments
+    /// * `rootfs` - String slice of the guest root directory path.
+    /// * `target` - Guest path the symlink must point to, usually [`MTAB_TARGET`].
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the symlink already pointed to `target` or was fixed.
+    /// * `Err` - If the stale entry could not be removed or the symlink not created.
+    pub(crate) fn fix_mtab_symlink(rootfs: &str, target: &Path) -> io::Result<()> {
+        let etc_path = Path::new(rootfs).join("etc");
+        let mtab_path = etc_path.join("mtab");
+        let is_fixed = || fs::read_link(&mtab_path).is_ok_and(|t| t == target);
+
+        if is_fixed() {
+            return Ok(());
+        }
+
+        // Another run may be removing the same directory, so entries vanishing
+        // under us are expected; loop until it is gone.
+        while fs::symlink_metadata(&mtab_path).is_ok_and(|md| md.is_dir()) {
+            match fs::remove_dir_all(&mtab_path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound && !is_fixed() => return Err(e),
+                _ => {}
+            }
+        }
+
+        let tmp_path = etc_path.join(format!(
+            ".mtab.{}.{}.tmp",
+            std::process::id(),
+            NEXT_MTAB_TMP.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        unix::fs::symlink(target, &tmp_path)
+            .and_then(|_| fs::rename(&tmp_path, &mtab_path))
+            .or_else(|e| {
+                let _ = fs::remove_file(&tmp_path);
+                if is_fixed() {
+                    Ok(())
+                } else {
+                    Err(e)
+                }
+            })
     }
 }
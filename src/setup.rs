@@ -0,0 +1,76 @@
+//! # Rootfs Setup Module
+//!
+//! This module wraps the usual first-run bootstrap into a single call:
+//! download the rootfs archive, extract it, validate the result, apply the
+//! post-extraction fixups and print the closing message. Every step reuses the
+//! public helpers of the other modules, so the behavior matches a hand-written
+//! setup flow.
+
+use crate::macros::{log_info, log_warn};
+use crate::sandbox::{SandBox, MTAB_TARGET};
+use crate::{
+    app_name, default_cache, download_file_with, extract_bootstrap_with, resolved_rootfs,
+    success_finish_setup, validate_rootfs, ArchiveFormat, DownloadOptions, ExtractOptions,
+};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// Makes sure the default rootfs is installed, bootstrapping it if needed.
+///
+/// If [`resolved_rootfs`] already passes [`validate_rootfs`], nothing is done.
+/// Otherwise the archive is downloaded into [`default_cache`] (revalidating
+/// a previous download instead of fetching it again), extracted, validated,
+/// and `/etc/mtab` is fixed; [`success_finish_setup`] is printed at the end.
+///
+/// # Arguments
+/// * `url` - The rootfs archive URL.
+/// * `format_hint` - The archive compression, for URLs whose file name does
+///   not end in a known extension. When `None`, it is taken from the URL.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The base directory to use as `SandBoxConfig::rootfs`.
+/// * `Err` - If the download or extraction fails, or the result is not a valid rootfs.
+pub fn ensure_rootfs(
+    url: &str,
+    format_hint: Option<ArchiveFormat>,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let base = resolved_rootfs();
+
+    if validate_rootfs(&base).is_ok() {
+        log_info!("Rootfs at {} is already set up", base.display());
+        return Ok(base);
+    }
+
+    let url_name = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|u| u.rsplit('/').next())
+        .filter(|name| !name.is_empty());
+    let filename = match (url_name, format_hint) {
+        (Some(name), None) => name.to_string(),
+        (_, Some(ArchiveFormat::Tar)) => "rootfs.tar".to_string(),
+        (_, Some(format)) => format!("rootfs.tar.{}", format.extension()),
+        (None, None) => return Err(format!("Cannot derive an archive name from {url}").into()),
+    };
+
+    let download = DownloadOptions {
+        revalidate: true,
+        ..Default::default()
+    };
+    let archive = download_file_with(url, default_cache(), &filename, &download)?.path;
+
+    let extract = ExtractOptions {
+        format: format_hint,
+        ..Default::default()
+    };
+    extract_bootstrap_with(archive, base.clone(), &extract)?;
+    validate_rootfs(&base)?;
+
+    let rootfs = base.join("rootfs");
+    if let Err(e) = SandBox::fix_mtab_symlink(&rootfs.to_string_lossy(), Path::new(MTAB_TARGET)) {
+        log_warn!("Failed to fix mtab symlink: {e}");
+    }
+
+    success_finish_setup(&app_name())?;
+    Ok(base)
+}
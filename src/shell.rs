@@ -0,0 +1,52 @@
+//! # Shell Quoting Module
+//!
+//! This module builds guest command strings for [`crate::SandBoxConfig::run_cmd`],
+//! which is executed with `/bin/sh -c`. Quoting follows the POSIX `sh` rules, so
+//! dynamic input (paths with spaces, quotes, `$`, globs) reaches the guest
+//! program as a single literal argument.
+
+/// Quotes a string so that `/bin/sh` reads it back as one literal word.
+///
+/// Strings made only of characters that are never special to the shell are
+/// returned unchanged. Anything else is wrapped in single quotes, where every
+/// character is literal; embedded single quotes are written as `'\''`. A `=`
+/// is always quoted, since a leading `NAME=value` word is an assignment.
+///
+/// # Arguments
+/// * `arg` - The raw argument.
+///
+/// # Returns
+/// The quoted argument; `''` for an empty string.
+pub fn shell_escape(arg: &str) -> String {
+    if arg.is_empty() {
+        return "''".to_string();
+    }
+
+    let safe = arg
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-+@%:,./".contains(c));
+
+    if safe {
+        return arg.to_string();
+    }
+
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+/// Quotes every argument with [`shell_escape`] and joins them with spaces.
+///
+/// # Arguments
+/// * `args` - The program and its arguments.
+///
+/// # Returns
+/// A command line suitable for `run_cmd`.
+pub fn shell_join<I, S>(args: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    args.into_iter()
+        .map(|arg| shell_escape(arg.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
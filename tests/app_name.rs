@@ -0,0 +1,15 @@
+use sandbox_utils::*;
+
+#[test]
+fn test_app_name_override() {
+    set_app_name("alpack");
+    sandbox_init("ALPack", "x86_64").expect("Init failed");
+    set_app_name("ignorado");
+
+    assert_eq!(app_name(), "alpack");
+
+    let result: Result<(), Box<dyn std::error::Error>> = invalid_arg!("aports", "foo");
+    let err_msg = result.unwrap_err().to_string();
+    println!("\n\x1b[1;31m{}\x1b[0m", err_msg);
+    assert!(err_msg.contains("alpack: aports"));
+}
@@ -0,0 +1,111 @@
+use sandbox_utils::*;
+use std::fs;
+use std::path::PathBuf;
+
+pub fn test_file(name: &str) -> PathBuf {
+    let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    p.push("files");
+    p.push(name);
+    p
+}
+
+#[test]
+fn test1_bind_set_build() {
+    let binds = BindSet::new()
+        .ro("/etc/ssl", "/etc/ssl")
+        .rw("/tmp/Meus Jogos", "/games")
+        .try_ro("/usr/share/icons", "/usr/share/icons")
+        .dev("/dev/null")
+        .build()
+        .expect("Valid binds were rejected");
+
+    assert_eq!(binds.len(), 4);
+    assert!(binds[0].read_only && !binds[0].optional);
+    assert_eq!(binds[1].source, PathBuf::from("/tmp/Meus Jogos"));
+    assert!(!binds[1].read_only);
+    assert!(binds[2].optional);
+    assert!(binds[3].device);
+    assert_eq!(binds[3].dest, PathBuf::from("/dev/null"));
+    println!("\x1b[1;32m--> Construção do BindSet Passou!\x1b[0m");
+}
+
+#[test]
+fn test2_bind_set_invalid() {
+    assert!(BindSet::new().ro("", "/etc/ssl").build().is_err());
+    assert!(BindSet::new().rw("/tmp", "").build().is_err());
+    assert!(BindSet::new().rw("/tmp", "relativo").build().is_err());
+    println!("\x1b[1;32m--> Validação do BindSet Passou!\x1b[0m");
+}
+
+#[test]
+fn test3_bind_set_run() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_bind_set");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let source = dest.join("com espaço");
+    fs::create_dir_all(&source).expect("Failed");
+
+    let binds = BindSet::new()
+        .rw(&source, "/mnt/dados")
+        .try_ro("/inexistente", "/mnt/nada")
+        .build()
+        .expect("Failed");
+
+    for tool in [USE_PROOT, USE_BWRAP] {
+        set_sandbox_tool(tool).expect("Failed");
+        SandBox::run(SandBoxConfig {
+            rootfs: dest.clone(),
+            rootfs_tool: tool.to_string(),
+            tool_target: resolve_tool(tool).expect("Failed"),
+            run_cmd: "true".to_string(),
+            binds: binds.clone(),
+            ..Default::default()
+        })
+        .expect("Failed");
+    }
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test4_paths_with_spaces() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test rootfs com espaço");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let source = dest.join("Meus Jogos");
+    fs::create_dir_all(&source).expect("Failed");
+
+    for tool in [USE_PROOT, USE_BWRAP] {
+        set_sandbox_tool(tool).expect("Failed");
+        let config = SandBoxConfig {
+            rootfs: dest.clone(),
+            rootfs_tool: tool.to_string(),
+            tool_target: resolve_tool(tool).expect("Failed"),
+            run_cmd: "true".to_string(),
+            binds: BindSet::new()
+                .ro(&source, "/mnt/Meus Jogos")
+                .build()
+                .expect("Failed"),
+            ..Default::default()
+        };
+
+        let argv = SandBox::command_argv(&config).expect("Failed");
+        let rootfs = dest.join("rootfs").to_string_lossy().into_owned();
+        assert!(argv.contains(&rootfs), "{tool}: rootfs path was split");
+        match tool {
+            USE_BWRAP => assert!(argv
+                .windows(3)
+                .any(|w| w == ["--ro-bind", source.to_str().unwrap(), "/mnt/Meus Jogos"])),
+            _ => assert!(argv.contains(&format!("--bind={}:/mnt/Meus Jogos", source.display()))),
+        }
+        assert!(
+            !argv.iter().any(|a| a.contains('"')),
+            "{tool}: stray quotes"
+        );
+
+        SandBox::run(config).expect("Failed");
+    }
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Caminhos com Espaços Passou!\x1b[0m");
+}
@@ -0,0 +1,150 @@
+use sandbox_utils::*;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test1_migrate_legacy_config() {
+    let dir = PathBuf::from("/tmp/test_config_migration");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed");
+
+    let path = dir.join("config.toml");
+    fs::write(
+        &path,
+        "tool = \"bwrap\"\nargs_bind = \"--bind /opt /opt\"\nuse_root = true\n",
+    )
+    .expect("Failed");
+
+    let table = migrate_config_file(&path).expect("Migration failed");
+    assert_eq!(table["version"].as_integer(), Some(CONFIG_VERSION));
+    assert_eq!(table["rootfs_tool"].as_str(), Some("bwrap"));
+    assert_eq!(table["args_bind"].as_str(), Some("--bind /opt /opt"));
+    assert_eq!(table["use_root"].as_bool(), Some(true));
+    assert!(!table.contains_key("tool"));
+
+    let written = fs::read_to_string(&path).expect("Failed");
+    println!("\n{written}");
+    assert!(written.contains("rootfs_tool = \"bwrap\""));
+
+    let mut current = table.clone();
+    assert!(!migrate_config(&mut current).expect("Failed"));
+    fs::remove_dir_all(dir).expect("Failed");
+}
+
+#[test]
+fn test2_reject_future_config() {
+    let mut table: toml::Table = format!("version = {}\n", CONFIG_VERSION + 1)
+        .parse()
+        .expect("Failed");
+
+    let err = migrate_config(&mut table).expect_err("Future versions must be rejected");
+    assert!(err
+        .downcast_ref::<UnsupportedConfigVersionError>()
+        .is_some());
+    println!("{err}");
+}
+
+#[test]
+fn test3_save_and_load_sandbox_config() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+    let dir = PathBuf::from("/tmp/test_config_save");
+    let _ = fs::remove_dir_all(&dir);
+    let path = dir.join("config.toml");
+
+    let config = SandBoxConfig {
+        rootfs: PathBuf::from("/tmp/Meu RootFS"),
+        rootfs_tool: USE_PROOT.to_string(),
+        use_root: true,
+        share_net: false,
+        hostname: Some("caixa".into()),
+        binds: BindSet::new()
+            .ro("/etc/ssl", "/etc/ssl")
+            .rw("/tmp/Meus Jogos", "/games")
+            .build()
+            .expect("Failed"),
+        run_cmd: "echo não salvo".into(),
+        ..SandBoxConfig::default()
+    };
+    config.save(&path).expect("Save failed");
+
+    let written = fs::read_to_string(&path).expect("Failed");
+    println!("\n{written}");
+    assert!(written.contains(&format!("version = {CONFIG_VERSION}")));
+    assert!(written.contains("rootfs = \"/tmp/Meu RootFS\""));
+    assert!(!written.contains("run_cmd"));
+
+    let loaded = SandBoxConfig::load(&path).expect("Load failed");
+    assert_eq!(loaded.rootfs, config.rootfs);
+    assert_eq!(loaded.rootfs_tool, USE_PROOT);
+    assert!(loaded.use_root && !loaded.share_net);
+    assert_eq!(loaded.hostname.as_deref(), Some("caixa"));
+    assert_eq!(loaded.binds, config.binds);
+    assert!(loaded.run_cmd.is_empty());
+
+    fs::remove_dir_all(dir).expect("Failed");
+    println!("\x1b[1;32m--> Salvar e Carregar Configuração Passou!\x1b[0m");
+}
+
+#[test]
+fn test4_load_partial_sandbox_config() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+    let dir = PathBuf::from("/tmp/test_config_partial");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed");
+
+    let path = dir.join("config.toml");
+    fs::write(&path, "tool = \"proot\"\nuse_root = true\nchave_nova = 1\n").expect("Failed");
+
+    let loaded = SandBoxConfig::load(&path).expect("Legacy file must load");
+    let defaults = SandBoxConfig::default();
+    assert_eq!(loaded.rootfs_tool, "proot");
+    assert!(loaded.use_root);
+    assert_eq!(loaded.rootfs, defaults.rootfs);
+    assert_eq!(loaded.share_net, defaults.share_net);
+    assert_eq!(loaded.fix_mtab, defaults.fix_mtab);
+
+    fs::write(&path, "use_root = \"sim\"\n").expect("Failed");
+    assert!(SandBoxConfig::load(&path).is_err());
+
+    fs::remove_dir_all(dir).expect("Failed");
+    println!("\x1b[1;32m--> Configuração Parcial Passou!\x1b[0m");
+}
+
+#[test]
+fn test5_load_unversioned_config_with_binds() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+    let dir = PathBuf::from("/tmp/test_config_unversioned_binds");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed");
+
+    let path = dir.join("config.toml");
+    fs::write(
+        &path,
+        "args_bind = \"--bind /opt /opt\"\n\
+         [[binds]]\nsource = \"/etc/ssl\"\ndest = \"/etc/ssl\"\nread_only = true\n",
+    )
+    .expect("Failed");
+
+    let loaded = SandBoxConfig::load(&path).expect("Unversioned binds must load");
+    assert_eq!(loaded.args_bind, "--bind /opt /opt");
+    assert_eq!(
+        loaded.binds,
+        BindSet::new()
+            .ro("/etc/ssl", "/etc/ssl")
+            .build()
+            .expect("Failed")
+    );
+
+    let written = fs::read_to_string(&path).expect("Failed");
+    assert!(written.contains(&format!("version = {CONFIG_VERSION}")));
+    assert!(!dir.join("config.toml.tmp").exists());
+    let reloaded = SandBoxConfig::load(&path).expect("Upgraded file must load");
+    assert_eq!(reloaded.args_bind, loaded.args_bind);
+    assert_eq!(reloaded.binds, loaded.binds);
+
+    fs::remove_dir_all(dir).expect("Failed");
+    println!("\x1b[1;32m--> Configuração Sem Versão Com Binds Passou!\x1b[0m");
+}
@@ -0,0 +1,38 @@
+use sandbox_utils::*;
+use std::fs;
+use std::path::PathBuf;
+
+pub fn test_file(name: &str) -> PathBuf {
+    let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    p.push("files");
+    p.push(name);
+    p
+}
+
+#[test]
+#[cfg(all(feature = "gz", target_arch = "x86_64"))]
+fn test_diagnose_missing_loader() {
+    let dest = PathBuf::from("/tmp/test_diagnose");
+    let _ = fs::remove_dir_all(&dest);
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    assert_eq!(diagnose_shell(&dest), None, "A complete rootfs must pass");
+
+    for entry in fs::read_dir(dest.join("rootfs/lib"))
+        .expect("Failed")
+        .flatten()
+    {
+        if entry.file_name().to_string_lossy().starts_with("ld-") {
+            fs::remove_file(entry.path()).expect("Failed");
+        }
+    }
+
+    let hint = diagnose_shell(&dest).expect("A missing loader must be reported");
+    println!("\n\x1b[1;33m{hint}\x1b[0m");
+    assert!(hint.contains("dynamic loader"));
+
+    fs::remove_dir_all(dest.join("rootfs/bin")).expect("Failed");
+    let hint = diagnose_shell(&dest).expect("A missing shell must be reported");
+    assert!(hint.contains("/bin/sh"));
+    fs::remove_dir_all(dest).expect("Failed");
+}
@@ -0,0 +1,117 @@
+use sandbox_utils::*;
+use std::env;
+
+#[test]
+fn test_help_text() {
+    unsafe {
+        env::set_var("NO_COLOR", "1");
+        env::set_var("COLUMNS", "60");
+    }
+
+    let help = get_help_text(
+        "alpack setup [OPTIONS]",
+        &[
+            ("-h, --help", "Show this help message and exit."),
+            (
+                "--tool=<name>",
+                "Sandbox tool used to run the rootfs. Accepts proot or bwrap and defaults to proot when omitted.",
+            ),
+        ],
+    )
+    .expect("Failed to render help");
+
+    println!("\n{help}");
+
+    assert!(help.contains("Usage: alpack setup [OPTIONS]"));
+    assert!(!help.contains("\x1b["), "NO_COLOR must disable ANSI codes");
+    assert!(help.contains("    -h, --help     Show this help message and exit."));
+    assert!(help.lines().all(|l| l.chars().count() <= 60));
+    assert!(help.lines().any(|l| l.starts_with("                   ")));
+}
+
+#[test]
+fn test_cmd_box_tiny_and_overflowing() {
+    let tiny = get_cmd_box("ls", Some(2), Some(4)).expect("Failed to render box");
+    println!("\n{tiny}");
+    let widths: Vec<usize> = tiny.lines().map(|l| l.chars().count()).collect();
+    assert!(widths.iter().all(|&w| w == widths[0]), "Box is misaligned");
+
+    let long = get_cmd_box("$ alpack setup --tool=bwrap --cache=/tmp/é", None, Some(20))
+        .expect("Failed to render box");
+    println!("{long}");
+    assert!(long.lines().count() > 3, "Long command should wrap");
+    assert!(long.lines().all(|l| l.chars().count() == 20));
+
+    let multibyte = get_cmd_box("ação çãõ", None, None).expect("Failed to render box");
+    assert!(multibyte.lines().all(|l| l.chars().count() == 50));
+}
+
+#[test]
+fn test_render_table_with_headers() {
+    let rows = vec![
+        ("rootfs.tar.zst".to_string(), "248 MiB".to_string()),
+        (
+            "status".to_string(),
+            "\x1b[1;31mOffline\x1b[0m -> \x1b[1;32mOnline\x1b[0m".to_string(),
+        ),
+        ("cache".to_string(), "1.2 GiB".to_string()),
+    ];
+
+    println!();
+    render_table_with(
+        ["Campo", "Valor"],
+        rows,
+        [Alignment::Left, Alignment::Right],
+    );
+}
+
+#[test]
+fn test_config_changes_only() {
+    #[derive(serde::Serialize)]
+    struct Conf {
+        os: String,
+        arch: String,
+        status: String,
+    }
+
+    let old = Conf {
+        os: "Debian".into(),
+        arch: "x86_64".into(),
+        status: "Online".into(),
+    };
+    let new = Conf {
+        os: "Debian".into(),
+        arch: "x86_64".into(),
+        status: "Active".into(),
+    };
+
+    assert_eq!(get_config_diff(&old, &new).len(), 3);
+
+    let changes = get_config_changes(&old, &new);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].0, "status");
+    assert!(get_config_changes(&old, &old).is_empty());
+}
+
+#[test]
+fn test_config_diff_ignoring() {
+    #[derive(serde::Serialize)]
+    struct Conf {
+        os: String,
+        tool_target: String,
+    }
+
+    let old = Conf {
+        os: "Debian".into(),
+        tool_target: "/home/a/.local/bin/bwrap".into(),
+    };
+    let new = Conf {
+        os: "Alpine".into(),
+        tool_target: "/home/b/.local/bin/bwrap".into(),
+    };
+
+    let rows = get_config_diff_ignoring(&old, &new, &["tool_target"]);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].0, "os");
+    assert_eq!(get_config_diff_ignoring(&old, &new, &[]).len(), 2);
+}
@@ -19,3 +19,46 @@ fn test_set_tool_unsupported_arch() {
         assert!(msg.contains("not found and no binary available for armv7l"));
     }
 }
+
+#[test]
+fn test_tools_not_downloadable_for_armv7l() {
+    let arch_env = "ALPACK_ARCH_FORCE";
+    unsafe {
+        env::set_var(arch_env, "armv7l");
+    }
+
+    sandbox_init("ArchLinux", arch_env).expect("Init failed");
+
+    for tool in [USE_PROOT, USE_BWRAP] {
+        assert!(
+            !can_use(tool).downloadable,
+            "no {tool} binary is published for armv7l"
+        );
+    }
+    assert!(!can_use("noexist").downloadable);
+}
+
+#[test]
+fn test_supported_tools_list() {
+    assert_eq!(supported_tools(), [USE_PROOT, USE_BWRAP]);
+
+    for tool in supported_tools() {
+        for arch in ["aarch64", "armv7l", "riscv64"] {
+            assert!(!tool_has_download(tool, arch));
+        }
+    }
+    assert!(!tool_has_download("noexist", "x86_64"));
+    println!("\x1b[1;32m--> Lista de Ferramentas Suportadas Passou!\x1b[0m");
+}
+
+#[test]
+#[ignore = "LINK_OPTIONS still needs the SHA-256 of the published x86_64 binaries"]
+fn test_tools_downloadable_for_x86_64() {
+    for tool in supported_tools() {
+        assert!(
+            tool_has_download(tool, "x86_64"),
+            "{tool} must have a pinned x86_64 binary"
+        );
+    }
+    println!("\x1b[1;32m--> Ferramentas Baixaveis em x86_64 Passou!\x1b[0m");
+}
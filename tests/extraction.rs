@@ -1,6 +1,47 @@
-use sandbox_utils::{download_file, extract_bootstrap};
+use sandbox_utils::{
+    archive_roots, download_file, download_file_with, download_file_with_progress,
+    extract_bootstrap, extract_bootstrap_with, extract_bootstrap_with_progress,
+    extract_buffer_size, set_extract_buffer_size, validate_rootfs, ArchiveFormat, CancelledError,
+    ChecksumMismatchError, DestinationNotEmptyError, DownloadCallback, DownloadOptions,
+    ExtractOptions, InvalidRootfsError, ProgressOptions,
+};
 use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Answers the n-th request with the n-th `(status, body, sent)` reply, then
+/// keeps repeating the last one. `sent` bytes of the body are written, after a
+/// `Content-Length` of the full body, so a short value drops the connection
+/// mid-transfer. Returns the URL and the request counter.
+pub fn serve_sequence(replies: Vec<(u16, Vec<u8>, usize)>) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let url = format!("http://{}/file", listener.local_addr().expect("Failed"));
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut request = [0u8; 4096];
+            let _ = stream.read(&mut request);
+
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            let (status, body, sent) = &replies[n.min(replies.len() - 1)];
+            let head = format!(
+                "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(&body[..*sent]);
+        }
+    });
+
+    (url, hits)
+}
 
 pub fn test_file(name: &str) -> PathBuf {
     let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -9,6 +50,83 @@ pub fn test_file(name: &str) -> PathBuf {
     p
 }
 
+/// Builds a tiny uncompressed rootfs tarball holding `bin/busybox` and
+/// `etc/hostname`, for the formats without a committed fixture.
+pub fn small_rootfs_tar() -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (path, mode, data) in [
+        ("bin/busybox", 0o755, &b"#!/bin/sh\n"[..]),
+        ("etc/hostname", 0o644, &b"caixa\n"[..]),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(mode);
+        builder
+            .append_data(&mut header, path, data)
+            .expect("Failed to append");
+    }
+    builder.into_inner().expect("Failed to build tar")
+}
+
+/// Serves `body` once over plain HTTP on a random local port and returns its URL.
+pub fn serve_once(body: Vec<u8>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let url = format!("http://{}/file", listener.local_addr().expect("Failed"));
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("Failed to accept");
+        let mut request = [0u8; 4096];
+        let _ = stream.read(&mut request);
+        let header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        let _ = stream.write_all(header.as_bytes());
+        let _ = stream.write_all(&body);
+    });
+
+    url
+}
+
+/// Serves `body` with an `ETag` over plain HTTP, answering conditional and
+/// ranged requests, and returns its URL.
+pub fn serve_cached(body: Vec<u8>, etag: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let url = format!("http://{}/file", listener.local_addr().expect("Failed"));
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut request = [0u8; 4096];
+            let n = stream.read(&mut request).unwrap_or(0);
+            let request = String::from_utf8_lossy(&request[..n]).to_lowercase();
+            let header = |name: &str| {
+                request
+                    .lines()
+                    .find_map(|l| l.strip_prefix(&format!("{name}: ")))
+                    .map(str::to_string)
+            };
+
+            let (status, part) = match (header("if-none-match"), header("range")) {
+                (Some(tag), _) if tag == etag => ("304 Not Modified", &body[..0]),
+                (_, Some(range)) if header("if-range").as_deref() == Some(etag) => {
+                    let start: usize = range["bytes=".len()..range.len() - 1].parse().unwrap();
+                    ("206 Partial Content", &body[start..])
+                }
+                _ => ("200 OK", &body[..]),
+            };
+            let head = format!(
+                "HTTP/1.1 {status}\r\nETag: {etag}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                part.len()
+            );
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(part);
+        }
+    });
+
+    url
+}
+
 #[test]
 #[cfg(feature = "gz")]
 fn test1_extract_gz() {
@@ -47,3 +165,814 @@ fn test4_download_test() {
     fs::remove_dir_all(dest).expect("Failed");
     println!("\x1b[1;32m--> Download Passou!\x1b[0m\n");
 }
+
+#[test]
+#[cfg(feature = "gz")]
+fn test5_validate_rootfs() {
+    let archive = test_file("rootfs.tar.gz");
+    let dest = PathBuf::from("/tmp/test_validate");
+    extract_bootstrap(archive, dest.clone()).expect("Failed to extract GZ");
+    validate_rootfs(&dest).expect("Extracted rootfs should be valid");
+
+    fs::remove_file(dest.join("rootfs/bin/sh")).expect("Failed");
+    let err = validate_rootfs(&dest).expect_err("Rootfs without /bin/sh must be rejected");
+    let invalid = err
+        .downcast_ref::<InvalidRootfsError>()
+        .expect("Expected InvalidRootfsError");
+    assert_eq!(invalid.1, vec!["bin/sh"]);
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Validação do RootFS Passou!\x1b[0m");
+}
+
+fn extract_renamed(fixture: &str, renamed: &str) {
+    let dir = PathBuf::from("/tmp/test_short_ext");
+    fs::create_dir_all(&dir).expect("Failed");
+    let archive = dir.join(renamed);
+    fs::copy(test_file(fixture), &archive).expect("Failed to copy fixture");
+
+    let dest = dir.join(renamed.replace('.', "_"));
+    extract_bootstrap(archive, dest.clone()).expect("Failed to extract renamed archive");
+    assert!(dest.join("rootfs/etc").is_dir());
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+#[cfg(all(feature = "gz", feature = "xz", feature = "zst"))]
+fn test6_extract_short_extensions() {
+    extract_renamed("rootfs.tar.gz", "rootfs.tgz");
+    extract_renamed("rootfs.tar.xz", "rootfs.txz");
+    extract_renamed("rootfs.tar.zst", "rootfs.tzst");
+    let _ = fs::remove_dir_all("/tmp/test_short_ext");
+    println!("\x1b[1;32m--> Extração .tgz/.txz/.tzst Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(feature = "zst")]
+fn test7_extract_custom_buffer() {
+    set_extract_buffer_size(0);
+    assert!(
+        extract_buffer_size() > 0,
+        "A zero buffer size must be ignored"
+    );
+
+    set_extract_buffer_size(1024 * 1024);
+    assert_eq!(extract_buffer_size(), 1024 * 1024);
+
+    let dest = PathBuf::from("/tmp/test_buffer");
+    extract_bootstrap(test_file("rootfs.tar.zst"), dest.clone()).expect("Failed to extract");
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Extração com Buffer Customizado Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(feature = "gz")]
+fn test8_extract_progress_style() {
+    let default = ProgressOptions::default().to_template();
+    assert!(default.ends_with("{bytes}/{total_bytes} ({eta})"));
+
+    let progress = ProgressOptions {
+        show_rate: true,
+        show_eta: false,
+        show_percent: true,
+        template: None,
+    };
+    let template = progress.to_template();
+    assert!(template.ends_with("{percent}% {bytes_per_sec}"));
+
+    let options = ExtractOptions {
+        progress,
+        ..Default::default()
+    };
+    let dest = PathBuf::from("/tmp/test_progress_style");
+    extract_bootstrap_with(test_file("rootfs.tar.gz"), dest.clone(), &options)
+        .expect("Failed to extract");
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Estilo de Progresso Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(feature = "xz")]
+fn test9_extract_cancelled() {
+    let options = ExtractOptions {
+        cancel: Some(Arc::new(AtomicBool::new(true))),
+        ..Default::default()
+    };
+    let dest = PathBuf::from("/tmp/test_cancel");
+    let _ = fs::remove_dir_all(&dest);
+
+    let err = extract_bootstrap_with(test_file("rootfs.tar.xz"), dest.clone(), &options)
+        .expect_err("A raised cancel flag must abort the extraction");
+    assert!(err.downcast_ref::<CancelledError>().is_some());
+    assert!(
+        !dest.join("rootfs").exists(),
+        "Cancelled extraction left a partial rootfs behind"
+    );
+
+    let _ = fs::remove_dir_all(dest);
+    println!("\x1b[1;32m--> Cancelamento da Extração Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(feature = "gz")]
+fn test10_extraction_summary() {
+    let dest = PathBuf::from("/tmp/test_summary");
+    let summary = extract_bootstrap_with(
+        test_file("rootfs.tar.gz"),
+        dest.clone(),
+        &ExtractOptions::default(),
+    )
+    .expect("Failed to extract");
+
+    println!("Summary: {:?}", summary);
+    assert!(summary.entries > 0, "Extraction produced no entries");
+    assert!(summary.total_bytes > 0, "Extraction produced no data");
+    assert!(
+        !summary.elapsed.is_zero(),
+        "Extraction time was not measured"
+    );
+    validate_rootfs(&dest).expect("Extracted rootfs should be valid");
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Resumo da Extração Passou!\x1b[0m");
+}
+
+#[test]
+fn test11_download_streaming_hash() {
+    use sha2::{Digest, Sha256};
+
+    let body: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+    let expected = format!("{:x}", Sha256::digest(&body));
+    let url = serve_once(body.clone());
+
+    let last = Arc::new(Mutex::new((0u64, 0u64, None)));
+    let seen = Arc::clone(&last);
+    let options = DownloadOptions {
+        hash: true,
+        on_progress: Some(DownloadCallback::new(move |p| {
+            *seen.lock().unwrap() = (p.downloaded, p.total, p.sha256());
+        })),
+        ..Default::default()
+    };
+
+    let dest = PathBuf::from("/tmp/test_download_hash");
+    let _ = fs::remove_dir_all(&dest);
+    let result =
+        download_file_with(&url, dest.clone(), "data.bin", &options).expect("Failed to download");
+    assert_eq!(result.sha256.as_deref(), Some(expected.as_str()));
+
+    let (downloaded, total, digest) = last.lock().unwrap().clone();
+    assert_eq!(downloaded, body.len() as u64);
+    assert_eq!(total, body.len() as u64);
+    assert_eq!(digest.as_deref(), Some(expected.as_str()));
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Hash Progressivo do Download Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(feature = "gz")]
+fn test12_extract_require_empty() {
+    let dest = PathBuf::from("/tmp/test_require_empty");
+    let _ = fs::remove_dir_all(&dest);
+    let options = ExtractOptions {
+        require_empty: true,
+        ..Default::default()
+    };
+
+    extract_bootstrap_with(test_file("rootfs.tar.gz"), dest.clone(), &options)
+        .expect("An absent destination counts as empty");
+
+    let err = extract_bootstrap_with(test_file("rootfs.tar.gz"), dest.clone(), &options)
+        .expect_err("A populated rootfs must be rejected");
+    assert!(err.downcast_ref::<DestinationNotEmptyError>().is_some());
+
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Merging stays the default");
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Destino Vazio Obrigatório Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(feature = "xz")]
+fn test13_extract_forced_format() {
+    let dir = PathBuf::from("/tmp/test_forced_format");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed");
+
+    let renamed = dir.join("rootfs.bin");
+    fs::copy(test_file("rootfs.tar.xz"), &renamed).expect("Failed to copy archive");
+
+    let wrong = ExtractOptions {
+        format: Some(ArchiveFormat::Zst),
+        ..Default::default()
+    };
+    assert!(extract_bootstrap_with(renamed.clone(), dir.join("wrong"), &wrong).is_err());
+
+    let options = ExtractOptions {
+        format: Some(ArchiveFormat::Xz),
+        ..Default::default()
+    };
+    let summary =
+        extract_bootstrap_with(renamed, dir.clone(), &options).expect("Failed to extract");
+    assert!(summary.entries > 0);
+    fs::remove_dir_all(dir).expect("Failed");
+    println!("\x1b[1;32m--> Formato Forçado Passou!\x1b[0m");
+}
+
+#[test]
+fn test14_download_result() {
+    let body = b"conteudo de teste".to_vec();
+    let url = serve_once(body.clone());
+    let dest = PathBuf::from("/tmp/test_download_result");
+    let _ = fs::remove_dir_all(&dest);
+
+    let options = DownloadOptions {
+        hash: true,
+        ..Default::default()
+    };
+    let first =
+        download_file_with(&url, dest.clone(), "data.bin", &options).expect("Failed to download");
+    assert_eq!(first.path, dest.join("data.bin"));
+    assert_eq!(first.filename, "data.bin");
+    assert_eq!(first.size, body.len() as u64);
+    assert!(!first.skipped);
+    assert!(!first.elapsed.is_zero());
+
+    let second = download_file_with(
+        "http://127.0.0.1:1/unused",
+        dest.clone(),
+        "data.bin",
+        &options,
+    )
+    .expect("Existing file must be skipped");
+    assert!(second.skipped);
+    assert_eq!(second.size, first.size);
+    assert_eq!(second.sha256, first.sha256);
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Resultado do Download Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(all(feature = "gz", feature = "xz"))]
+fn test15_archive_roots() {
+    let gz = archive_roots(&test_file("rootfs.tar.gz")).expect("Failed to list GZ");
+    let xz = archive_roots(&test_file("rootfs.tar.xz")).expect("Failed to list XZ");
+
+    println!("Roots: {:?}", gz);
+    for roots in [&gz, &xz] {
+        assert!(roots.contains(&"bin".to_string()));
+        assert!(roots.contains(&"etc".to_string()));
+        assert!(!roots.iter().any(|r| r == "." || r.contains('/')));
+    }
+    assert!(archive_roots(&test_file("inexistente.tar.gz")).is_err());
+    assert!(archive_roots(&test_file("rootfs.rar")).is_err());
+    println!("\x1b[1;32m--> Raízes do Arquivo Passou!\x1b[0m");
+}
+
+#[test]
+fn test16_download_revalidate_and_resume() {
+    use sha2::{Digest, Sha256};
+
+    let body: Vec<u8> = (0..100_000u32).map(|i| (i % 253) as u8).collect();
+    let expected = format!("{:x}", Sha256::digest(&body));
+    let url = serve_cached(body.clone(), "\"v1\"");
+    let dest = PathBuf::from("/tmp/test_download_cache");
+    let _ = fs::remove_dir_all(&dest);
+
+    let options = DownloadOptions {
+        revalidate: true,
+        hash: true,
+        ..Default::default()
+    };
+
+    let first = download_file_with(&url, dest.clone(), "data.bin", &options).expect("Failed");
+    assert!(!first.skipped && !first.cache_hit && !first.resumed);
+    assert!(dest.join("data.bin.meta").exists(), "Missing sidecar");
+
+    let second = download_file_with(&url, dest.clone(), "data.bin", &options).expect("Failed");
+    assert!(second.skipped && second.cache_hit);
+    assert_eq!(second.sha256.as_deref(), Some(expected.as_str()));
+
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .open(dest.join("data.bin"))
+        .expect("Failed");
+    file.set_len(40_000).expect("Failed to truncate");
+
+    let third = download_file_with(&url, dest.clone(), "data.bin", &options).expect("Failed");
+    assert!(third.resumed && !third.cache_hit);
+    assert_eq!(third.size, body.len() as u64);
+    assert_eq!(third.sha256.as_deref(), Some(expected.as_str()));
+    assert_eq!(fs::read(dest.join("data.bin")).expect("Failed"), body);
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Cache e Retomada do Download Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(feature = "zst")]
+fn test17_extract_background_decode() {
+    let plain = PathBuf::from("/tmp/test_decode_plain");
+    let background = PathBuf::from("/tmp/test_decode_background");
+    let _ = fs::remove_dir_all(&plain);
+    let _ = fs::remove_dir_all(&background);
+
+    let expected = extract_bootstrap_with(
+        test_file("rootfs.tar.zst"),
+        plain.clone(),
+        &Default::default(),
+    )
+    .expect("Failed to extract");
+    let options = ExtractOptions {
+        background_decode: Some(true),
+        zstd_window_log_max: Some(31),
+        ..Default::default()
+    };
+    let summary = extract_bootstrap_with(test_file("rootfs.tar.zst"), background.clone(), &options)
+        .expect("Failed to extract in background");
+
+    assert_eq!(
+        (summary.entries, summary.total_bytes),
+        (expected.entries, expected.total_bytes)
+    );
+    assert!(background.join("rootfs/bin/busybox").exists());
+    fs::remove_dir_all(plain).expect("Failed");
+    fs::remove_dir_all(background).expect("Failed");
+    println!("\x1b[1;32m--> Decodificação em Segundo Plano Passou!\x1b[0m");
+}
+
+#[test]
+fn test18_download_retry() {
+    let body: Vec<u8> = (0..50_000u32).map(|i| (i % 251) as u8).collect();
+    let dest = PathBuf::from("/tmp/test_download_retry");
+    let _ = fs::remove_dir_all(&dest);
+
+    let (url, hits) = serve_sequence(vec![
+        (503, b"busy".to_vec(), 4),
+        (200, body.clone(), 20_000),
+        (200, body.clone(), body.len()),
+    ]);
+    let result = download_file_with(&url, dest.clone(), "data.bin", &Default::default())
+        .expect("Transient errors must be retried");
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+    assert_eq!(result.size, body.len() as u64);
+    assert_eq!(fs::read(dest.join("data.bin")).expect("Failed"), body);
+
+    let (url, hits) = serve_sequence(vec![(404, b"none".to_vec(), 4)]);
+    assert!(download_file_with(&url, dest.clone(), "missing.bin", &Default::default()).is_err());
+    assert_eq!(hits.load(Ordering::SeqCst), 1, "404 must not be retried");
+
+    let (url, hits) = serve_sequence(vec![(500, b"fail".to_vec(), 4)]);
+    let options = DownloadOptions {
+        attempts: Some(2),
+        ..Default::default()
+    };
+    assert!(download_file_with(&url, dest.clone(), "broken.bin", &options).is_err());
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    assert!(!dest.join("broken.bin").exists());
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Novas Tentativas do Download Passou!\x1b[0m");
+}
+
+#[test]
+fn test19_download_resume_partial() {
+    let body: Vec<u8> = (0..80_000u32).map(|i| (i % 241) as u8).collect();
+    let dest = PathBuf::from("/tmp/test_download_partial");
+    let _ = fs::remove_dir_all(&dest);
+    fs::create_dir_all(&dest).expect("Failed");
+
+    let url = serve_cached(body.clone(), "\"v2\"");
+    fs::write(dest.join("data.bin"), &body[..30_000]).expect("Failed");
+    fs::write(
+        dest.join("data.bin.meta"),
+        format!("etag: \"v2\"\ncontent-length: {}\n", body.len()),
+    )
+    .expect("Failed");
+
+    let result = download_file_with(&url, dest.clone(), "data.bin", &Default::default())
+        .expect("Failed to resume");
+    assert!(result.resumed && !result.skipped);
+    assert_eq!(fs::read(dest.join("data.bin")).expect("Failed"), body);
+    assert!(!dest.join("data.bin.meta").exists(), "Sidecar must go away");
+
+    let again =
+        download_file_with(&url, dest.clone(), "data.bin", &Default::default()).expect("Failed");
+    assert!(again.skipped);
+
+    let (url, hits) = serve_sequence(vec![
+        (200, body.clone(), 10_000),
+        (200, body.clone(), body.len()),
+    ]);
+    let options = DownloadOptions {
+        attempts: Some(1),
+        ..Default::default()
+    };
+    assert!(download_file_with(&url, dest.clone(), "plain.bin", &options).is_err());
+    assert!(
+        !dest.join("plain.bin").exists(),
+        "Unresumable partial must go"
+    );
+
+    fs::write(dest.join("plain.bin"), &body[..10_000]).expect("Failed");
+    fs::write(
+        dest.join("plain.bin.meta"),
+        format!("content-length: {}\n", body.len()),
+    )
+    .expect("Failed");
+    let restarted =
+        download_file_with(&url, dest.clone(), "plain.bin", &options).expect("Failed to restart");
+    assert!(!restarted.resumed && !restarted.skipped);
+    assert_eq!(fs::read(dest.join("plain.bin")).expect("Failed"), body);
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Retomada de Download Parcial Passou!\x1b[0m");
+}
+
+#[test]
+fn test20_download_expected_sha256() {
+    use sha2::{Digest, Sha256};
+
+    let body: Vec<u8> = (0..30_000u32).map(|i| (i % 239) as u8).collect();
+    let digest = format!("{:x}", Sha256::digest(&body));
+    let dest = PathBuf::from("/tmp/test_download_checksum");
+    let _ = fs::remove_dir_all(&dest);
+
+    let options = DownloadOptions {
+        expected_sha256: Some(digest.to_uppercase()),
+        ..Default::default()
+    };
+    let result = download_file_with(&serve_once(body.clone()), dest.clone(), "ok.bin", &options)
+        .expect("Matching digest was rejected");
+    assert_eq!(result.sha256.as_deref(), Some(digest.as_str()));
+
+    let wrong = DownloadOptions {
+        expected_sha256: Some("00".repeat(32)),
+        ..Default::default()
+    };
+    let err = download_file_with(&serve_once(body.clone()), dest.clone(), "bad.bin", &wrong)
+        .expect_err("Wrong digest must fail");
+    let mismatch = err
+        .downcast_ref::<ChecksumMismatchError>()
+        .expect("Expected ChecksumMismatchError");
+    assert_eq!(mismatch.actual, digest);
+    assert!(
+        !dest.join("bad.bin").exists(),
+        "Corrupt file must be deleted"
+    );
+
+    let err = download_file_with("http://127.0.0.1:1/unused", dest.clone(), "ok.bin", &wrong)
+        .expect_err("Skipped file must be checked too");
+    assert!(err.downcast_ref::<ChecksumMismatchError>().is_some());
+    assert!(!dest.join("ok.bin").exists());
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Verificação de Checksum do Download Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(feature = "bz2")]
+fn test21_extract_bz2() {
+    let dir = PathBuf::from("/tmp/test_bz2");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed");
+
+    let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::fast());
+    encoder
+        .write_all(&small_rootfs_tar())
+        .expect("Failed to compress");
+    let compressed = encoder.finish().expect("Failed to compress");
+
+    for name in ["rootfs.tar.bz2", "rootfs.tbz2"] {
+        let archive = dir.join(name);
+        fs::write(&archive, &compressed).expect("Failed");
+        let dest = dir.join(name.replace('.', "_"));
+        extract_bootstrap(archive, dest.clone()).expect("Failed to extract BZ2");
+        assert!(dest.join("rootfs/bin/busybox").exists());
+    }
+    fs::remove_dir_all(&dir).expect("Failed");
+
+    assert_eq!(
+        ArchiveFormat::from_extension("bz2"),
+        Some(ArchiveFormat::Bz2)
+    );
+    assert_eq!(ArchiveFormat::from_extension("bz3"), None);
+    println!("\x1b[1;32m--> Extração BZ2 Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(all(feature = "xz", feature = "zst"))]
+fn test22_extract_detect_magic() {
+    let dir = PathBuf::from("/tmp/test_magic");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed");
+
+    for (fixture, renamed) in [
+        ("rootfs.tar.xz", "rootfs.img"),
+        ("rootfs.tar.zst", "rootfs.tar.gz"),
+    ] {
+        let archive = dir.join(renamed);
+        fs::copy(test_file(fixture), &archive).expect("Failed to copy fixture");
+        let roots = archive_roots(&archive).expect("Failed to list roots");
+        assert!(roots.contains(&"etc".to_string()));
+
+        let dest = dir.join(renamed.replace('.', "_"));
+        extract_bootstrap(archive, dest.clone()).expect("Failed to detect the format");
+        assert!(dest.join("rootfs/etc").is_dir());
+    }
+
+    assert_eq!(
+        ArchiveFormat::from_magic(&[0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x00]),
+        Some(ArchiveFormat::Zst)
+    );
+    assert_eq!(ArchiveFormat::from_magic(b"BZh9"), Some(ArchiveFormat::Bz2));
+    assert_eq!(ArchiveFormat::from_magic(b"BZh"), None);
+
+    let text = dir.join("notes.bin");
+    fs::write(&text, "não é um arquivo").expect("Failed");
+    assert!(extract_bootstrap(text, dir.join("notes")).is_err());
+
+    fs::remove_dir_all(dir).expect("Failed");
+    println!("\x1b[1;32m--> Detecção por Número Mágico Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(feature = "gz")]
+fn test23_progress_callbacks() {
+    let body: Vec<u8> = (0..40_000u32).map(|i| (i % 233) as u8).collect();
+    let dest = PathBuf::from("/tmp/test_progress_callbacks");
+    let _ = fs::remove_dir_all(&dest);
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+    download_file_with_progress(
+        &serve_once(body.clone()),
+        dest.clone(),
+        "data.bin",
+        move |d, t| log.lock().unwrap().push((d, t)),
+    )
+    .expect("Failed to download");
+    let updates = seen.lock().unwrap().clone();
+    assert!(updates.windows(2).all(|w| w[0].0 <= w[1].0));
+    assert_eq!(
+        updates.last(),
+        Some(&(body.len() as u64, body.len() as u64))
+    );
+
+    let archive = test_file("rootfs.tar.gz");
+    let size = fs::metadata(&archive).expect("Failed").len();
+    let last = Arc::new(Mutex::new((0, 0)));
+    let log = last.clone();
+    let summary = extract_bootstrap_with_progress(archive, dest.join("rootfs"), move |r, t| {
+        let mut last = log.lock().unwrap();
+        assert!(r >= last.0, "Progress went backwards");
+        *last = (r, t);
+    })
+    .expect("Failed to extract");
+    assert!(summary.entries > 0);
+    assert_eq!(*last.lock().unwrap(), (size, size));
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Callbacks de Progresso Passou!\x1b[0m");
+}
+
+#[test]
+fn test24_extract_strip_components() {
+    use std::process::Command;
+
+    let dir = PathBuf::from("/tmp/test_strip_components");
+    let _ = fs::remove_dir_all(&dir);
+    extract_bootstrap(test_file("rootfs.tar.gz"), dir.join("plain")).expect("Failed to extract");
+
+    let wrapped = dir.join("wrapped.tar.gz");
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&wrapped)
+        .arg("-C")
+        .arg(dir.join("plain"))
+        .arg("rootfs")
+        .status()
+        .expect("Failed to run tar");
+    assert!(status.success());
+    assert_eq!(
+        archive_roots(&wrapped).expect("Failed to list roots"),
+        vec!["rootfs".to_string()]
+    );
+
+    let options = ExtractOptions {
+        strip_components: 1,
+        ..Default::default()
+    };
+    let summary = extract_bootstrap_with(wrapped.clone(), dir.join("stripped"), &options)
+        .expect("Failed to extract");
+    assert!(summary.entries > 0);
+    assert!(dir.join("stripped/rootfs/etc").is_dir());
+    assert!(!dir.join("stripped/rootfs/rootfs").exists());
+
+    let options = ExtractOptions {
+        strip_components: 64,
+        ..Default::default()
+    };
+    let summary =
+        extract_bootstrap_with(wrapped, dir.join("empty"), &options).expect("Failed to extract");
+    assert_eq!(summary.entries, 0);
+
+    fs::remove_dir_all(dir).expect("Failed");
+    println!("\x1b[1;32m--> Remoção de Componentes Passou!\x1b[0m");
+}
+
+/// In-memory tar archive used to craft hostile fixtures.
+#[cfg(feature = "gz")]
+type TarBuilder = tar::Builder<Vec<u8>>;
+
+/// Writes a `.tar.gz` whose entries are added by `fill`, bypassing the path
+/// checks of `tar::Builder` so hostile names can be stored as-is.
+#[cfg(feature = "gz")]
+fn hostile_archive(path: &PathBuf, fill: impl FnOnce(&mut TarBuilder)) {
+    let mut builder = tar::Builder::new(Vec::new());
+    fill(&mut builder);
+    let tar = builder.into_inner().expect("Failed to build tar");
+
+    let file = fs::File::create(path).expect("Failed to create archive");
+    let mut gz = flate2::write::GzEncoder::new(file, flate2::Compression::fast());
+    gz.write_all(&tar).expect("Failed to compress");
+    gz.finish().expect("Failed to compress");
+}
+
+/// Appends an entry with a raw `name`, and `link` as its link target.
+#[cfg(feature = "gz")]
+fn raw_entry(builder: &mut TarBuilder, kind: tar::EntryType, name: &str, link: &str) {
+    let mut header = tar::Header::new_old();
+    header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+    header.as_old_mut().linkname[..link.len()].copy_from_slice(link.as_bytes());
+    let data: &[u8] = match kind {
+        tar::EntryType::Regular => b"oops",
+        _ => b"",
+    };
+    header.set_entry_type(kind);
+    header.set_mode(0o644);
+    header.set_size(data.len() as u64);
+    header.set_cksum();
+    builder.append(&header, data).expect("Failed to append");
+}
+
+#[test]
+#[cfg(feature = "gz")]
+fn test25_extract_path_traversal() {
+    use sandbox_utils::PathTraversalError;
+    use tar::EntryType;
+
+    let dir = PathBuf::from("/tmp/test_path_traversal");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("outside")).expect("Failed");
+    let outside = dir.join("outside").to_string_lossy().into_owned();
+
+    let cases = [
+        ("dotdot", vec![(EntryType::Regular, "etc/../../escape", "")]),
+        (
+            "symlink",
+            vec![
+                (EntryType::Symlink, "etc", outside.as_str()),
+                (EntryType::Regular, "etc/escape", ""),
+            ],
+        ),
+        (
+            "hardlink",
+            vec![(EntryType::Link, "passwd", "../../../etc/passwd")],
+        ),
+    ];
+
+    for (name, entries) in cases {
+        let archive = dir.join(format!("{name}.tar.gz"));
+        hostile_archive(&archive, |b| {
+            for (kind, path, link) in entries {
+                raw_entry(b, kind, path, link);
+            }
+        });
+
+        let dest = dir.join(name);
+        let err = extract_bootstrap(archive, dest.clone()).expect_err("Escape not detected");
+        assert!(
+            err.downcast_ref::<PathTraversalError>().is_some(),
+            "{name}: {err}"
+        );
+        assert!(!dest.join("rootfs").exists());
+    }
+    assert!(!dir.join("escape").exists());
+    assert_eq!(
+        fs::read_dir(dir.join("outside")).expect("Failed").count(),
+        0
+    );
+
+    let archive = dir.join("absolute.tar.gz");
+    hostile_archive(&archive, |b| {
+        raw_entry(b, EntryType::Regular, "/etc/hostname", "")
+    });
+    extract_bootstrap(archive, dir.join("absolute")).expect("Failed to extract");
+    assert!(dir.join("absolute/rootfs/etc/hostname").is_file());
+
+    fs::remove_dir_all(dir).expect("Failed");
+    println!("\x1b[1;32m--> Proteção Contra Path Traversal Passou!\x1b[0m");
+}
+
+#[test]
+#[cfg(feature = "gz")]
+fn test26_extract_size_limit() {
+    use sandbox_utils::SizeLimitExceededError;
+
+    let dir = PathBuf::from("/tmp/test_size_limit");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed");
+
+    let bomb = dir.join("bomb.tar.gz");
+    hostile_archive(&bomb, |b| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(32 << 20);
+        header.set_mode(0o644);
+        b.append_data(&mut header, "zeros", std::io::repeat(0).take(32 << 20))
+            .expect("Failed to append");
+    });
+    assert!(fs::metadata(&bomb).expect("Failed").len() < 1 << 20);
+
+    let options = ExtractOptions {
+        max_uncompressed: Some(1 << 20),
+        ..Default::default()
+    };
+    let err =
+        extract_bootstrap_with(bomb, dir.join("bomb"), &options).expect_err("Limit not enforced");
+    assert!(err.downcast_ref::<SizeLimitExceededError>().is_some());
+    assert!(!dir.join("bomb/rootfs").exists());
+
+    let archive = test_file("rootfs.tar.gz");
+    let full = extract_bootstrap_with(
+        archive.clone(),
+        dir.join("full"),
+        &ExtractOptions::default(),
+    )
+    .expect("Failed");
+    let options = ExtractOptions {
+        max_uncompressed: Some(full.total_bytes),
+        ..Default::default()
+    };
+    let summary =
+        extract_bootstrap_with(archive, dir.join("exact"), &options).expect("Failed to extract");
+    assert_eq!(summary.total_bytes, full.total_bytes);
+
+    fs::remove_dir_all(dir).expect("Failed");
+    println!("\x1b[1;32m--> Limite de Descompressão Passou!\x1b[0m");
+}
+
+#[test]
+fn test27_extract_plain_tar() {
+    let dir = PathBuf::from("/tmp/test_plain_tar");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed");
+
+    let tar = small_rootfs_tar();
+    assert_eq!(ArchiveFormat::from_magic(&tar), Some(ArchiveFormat::Tar));
+    assert_eq!(ArchiveFormat::from_magic(&tar[..261]), None);
+    assert_eq!(
+        ArchiveFormat::from_extension("tar"),
+        Some(ArchiveFormat::Tar)
+    );
+
+    for name in ["rootfs.tar", "rootfs.img"] {
+        let archive = dir.join(name);
+        fs::write(&archive, &tar).expect("Failed");
+        let dest = dir.join(name.replace('.', "_"));
+        extract_bootstrap(archive, dest.clone()).expect("Failed to extract TAR");
+        assert!(dest.join("rootfs/bin/busybox").exists());
+    }
+    fs::remove_dir_all(&dir).expect("Failed");
+    println!("\x1b[1;32m--> Extração TAR Sem Compressão Passou!\x1b[0m");
+}
+
+#[test]
+fn test28_download_restart_on_416() {
+    let body: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+    let dest = PathBuf::from("/tmp/test_download_416");
+    let _ = fs::remove_dir_all(&dest);
+    fs::create_dir_all(&dest).expect("Failed");
+
+    fs::write(dest.join("data.bin"), vec![7u8; 30_000]).expect("Failed");
+    fs::write(
+        dest.join("data.bin.meta"),
+        "etag: \"v1\"\ncontent-length: 40000\n",
+    )
+    .expect("Failed");
+
+    let (url, hits) = serve_sequence(vec![(416, Vec::new(), 0), (200, body.clone(), body.len())]);
+    let options = DownloadOptions {
+        attempts: Some(1),
+        ..Default::default()
+    };
+    let result =
+        download_file_with(&url, dest.clone(), "data.bin", &options).expect("Failed to restart");
+    assert!(!result.resumed && !result.skipped);
+    assert_eq!(fs::read(dest.join("data.bin")).expect("Failed"), body);
+    assert!(!dest.join("data.bin.meta").exists());
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Reinício Após 416 Passou!\x1b[0m");
+}
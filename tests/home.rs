@@ -0,0 +1,20 @@
+use sandbox_utils::*;
+use std::env;
+
+#[test]
+fn test_init_without_home() {
+    unsafe {
+        env::remove_var("HOME");
+    }
+
+    let err = sandbox_init("ArchLinux", "ARCH").expect_err("Missing HOME must be rejected");
+    assert!(err.downcast_ref::<HomeNotSetError>().is_some(), "{err}");
+
+    unsafe {
+        env::set_var("HOME", "relativo");
+    }
+    let err = sandbox_init("ArchLinux", "ARCH").expect_err("Relative HOME must be rejected");
+    assert!(err.to_string().contains("HOME"), "{err}");
+    assert!(!std::path::Path::new("relativo").exists());
+    println!("\x1b[1;32m--> HOME Ausente Passou!\x1b[0m");
+}
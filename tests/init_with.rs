@@ -0,0 +1,29 @@
+use sandbox_utils::*;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_sandbox_init_with_custom_layout() {
+    let base = PathBuf::from("/tmp/test_init_with");
+    let _ = fs::remove_dir_all(&base);
+
+    sandbox_init_with(SandboxConfig {
+        app_name: "meuapp".into(),
+        app_arch: "x86_64".into(),
+        safe_home: base.join("home"),
+        config_dir: base.join("etc"),
+        config_file: base.join("etc/meuapp.toml"),
+        default_cache: base.join("cache"),
+        default_rootfs: base.join("rootfs"),
+        temp_cache: base.join("tmp"),
+    })
+    .expect("Init failed");
+
+    sandbox_init("Ignorado", "ARCH").expect("Second init must be a no-op");
+
+    assert_eq!(app_name(), "meuapp");
+    assert_eq!(config_file(), base.join("etc/meuapp.toml"));
+    assert_eq!(default_rootfs(), base.join("rootfs"));
+    assert!(config_dir().is_dir() && default_cache().is_dir());
+    fs::remove_dir_all(base).expect("Failed");
+}
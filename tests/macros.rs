@@ -54,3 +54,98 @@ fn test_macro_parse_value() {
     println!("\x1b[1;31m{}\x1b[0m\n", err_res);
     assert!(err_res.contains("requires a <pkg>"));
 }
+
+#[test]
+fn test_macro_parse_enum() {
+    sandbox_init("ALPack", "x86_64").expect("Init failed");
+
+    let tools = &[USE_PROOT, USE_BWRAP];
+
+    let val1 = parse_enum!("setup", "tool", "--tool=bwrap", tools).expect("Failed to parse =");
+    assert_eq!(val1, "bwrap");
+
+    let val2 = parse_enum!("setup", "tool", "--tool", Some("proot"), tools)
+        .expect("Failed to parse space");
+    assert_eq!(val2, "proot");
+
+    let res_err = parse_enum!("setup", "tool", "--tool=docker", tools);
+    let err_res = res_err.unwrap_err();
+    println!("\x1b[1;31m{}\x1b[0m\n", err_res);
+    assert!(err_res.contains("invalid <tool> 'docker'"));
+    assert!(err_res.contains("Valid options: proot, bwrap"));
+
+    let res_missing = parse_enum!("setup", "tool", "--tool=", tools);
+    assert!(res_missing.unwrap_err().contains("requires a <tool>"));
+}
+
+#[test]
+fn test_macro_parse_range() {
+    sandbox_init("ALPack", "x86_64").expect("Init failed");
+
+    let retries: u32 =
+        parse_range!("get", "retries", "--retries=3", 1..=10).expect("Failed to parse =");
+    assert_eq!(retries, 3);
+
+    let timeout: u64 = parse_range!("get", "secs", "--timeout", Some("60"), 1..=3600)
+        .expect("Failed to parse space");
+    assert_eq!(timeout, 60);
+
+    let out_of_range: Result<u32, String> = parse_range!("get", "retries", "--retries=42", 1..=10);
+    let err_range = out_of_range.unwrap_err();
+    println!("\x1b[1;31m{}\x1b[0m", err_range);
+    assert!(err_range.contains("out of range"));
+    assert!(err_range.contains("1..=10"));
+
+    let not_number: Result<u32, String> = parse_range!("get", "retries", "--retries=abc", 1..=10);
+    let err_parse = not_number.unwrap_err();
+    println!("\x1b[1;31m{}\x1b[0m\n", err_parse);
+    assert!(err_parse.contains("is not a valid number"));
+}
+
+#[test]
+fn test_macro_mutually_exclusive() {
+    sandbox_init("ALPack", "x86_64").expect("Init failed");
+
+    let ok: Result<(), Box<dyn std::error::Error>> =
+        mutually_exclusive!("setup", "--gz" => true, "--xz" => false, "--zst" => false);
+    assert!(ok.is_ok());
+
+    let res: Result<(), Box<dyn std::error::Error>> =
+        mutually_exclusive!("setup", "--gz" => true, "--xz" => true, "--zst" => true);
+    let err_msg = res.unwrap_err().to_string();
+
+    println!("\x1b[1;31m{}\x1b[0m\n", err_msg);
+    assert!(err_msg.contains(": setup: options '--gz', '--xz' and '--zst' cannot be used together"));
+    assert!(err_msg.contains("--help"));
+}
+
+#[test]
+fn test_macro_collect_positional() {
+    let args = "wget --tool proot -v curl --get=git - -- -literal vim".split(' ');
+
+    let positional = collect_positional!(args, &["--tool"]);
+    println!("\x1b[1;32m{:?}\x1b[0m", positional);
+    assert_eq!(positional, vec!["wget", "curl", "-", "-literal", "vim"]);
+
+    let owned: Vec<String> = vec!["a".into(), "--flag".into(), "b".into()];
+    assert_eq!(collect_positional!(owned), vec!["a", "b"]);
+}
+
+#[test]
+fn test_macro_collect_key_values() {
+    sandbox_init("ALPack", "x86_64").expect("Init failed");
+
+    let args = "run --env=LANG=C --env FOO=bar -v --env FOO=baz --env=EMPTY=".split(' ');
+    let env = collect_key_values!("run", "var", args, "--env").expect("Failed to collect");
+
+    println!("\x1b[1;32m{:?}\x1b[0m", env);
+    assert_eq!(env.len(), 3);
+    assert_eq!(env["LANG"], "C");
+    assert_eq!(env["FOO"], "baz");
+    assert_eq!(env["EMPTY"], "");
+
+    let res_err = collect_key_values!("run", "var", ["--env", "NOVALUE"], "--env");
+    let err_res = res_err.unwrap_err();
+    println!("\x1b[1;31m{}\x1b[0m\n", err_res);
+    assert!(err_res.contains("KEY=VALUE"));
+}
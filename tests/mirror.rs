@@ -0,0 +1,73 @@
+use sandbox_utils::*;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+/// Script served as the mirrored tool binary.
+const BODY: &[u8] = b"#!/bin/sh\nexit 0\n";
+
+#[test]
+fn test_tool_download_mirror() {
+    let base = PathBuf::from("/tmp/test_tool_mirror");
+    let _ = fs::remove_dir_all(&base);
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let mirror = format!("http://{}/espelho/", listener.local_addr().expect("Failed"));
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("Failed to accept");
+        let mut request = [0u8; 4096];
+        let n = stream.read(&mut request).unwrap_or(0);
+        let request = String::from_utf8_lossy(&request[..n]).into_owned();
+        let _ = tx.send(request.lines().next().unwrap_or_default().to_string());
+
+        let header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            BODY.len()
+        );
+        let _ = stream.write_all(header.as_bytes());
+        let _ = stream.write_all(BODY);
+    });
+
+    unsafe {
+        std::env::set_var(TOOL_MIRROR_ENV, &mirror);
+        std::env::set_var("PATH", "/usr/bin:/bin");
+    }
+    sandbox_init_with(SandboxConfig {
+        app_name: "meuapp".into(),
+        app_arch: "x86_64".into(),
+        safe_home: base.join("home"),
+        config_dir: base.join("config"),
+        config_file: base.join("config/config.toml"),
+        default_cache: base.join("cache"),
+        default_rootfs: base.join("rootfs"),
+        temp_cache: base.join("tmp"),
+    })
+    .expect("Init failed");
+    set_tool_install_dir(base.join("bin"));
+
+    assert!(set_tool_sha256(USE_PROOT, "abc").is_err());
+    let digest = format!("{:X}", Sha256::digest(BODY));
+    set_tool_sha256(USE_PROOT, &digest).expect("Failed to pin");
+    set_sandbox_tool(USE_PROOT).expect("Mirror download failed");
+
+    let request = rx.recv().expect("No request received");
+    assert_eq!(
+        request,
+        "GET /espelho/LinuxProativo/StaticHub/releases/download/proot/proot HTTP/1.1"
+    );
+    let mode = fs::metadata(base.join("bin/proot"))
+        .expect("Missing tool")
+        .permissions()
+        .mode();
+    assert_ne!(mode & 0o111, 0);
+    assert_eq!(tool_target(), base.join("bin/proot"));
+    fs::remove_dir_all(&base).expect("Failed");
+    println!("\x1b[1;32m--> Espelho de Download Passou!\x1b[0m");
+}
@@ -0,0 +1,166 @@
+use sandbox_utils::*;
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Reads a request head, which clients may send in several writes.
+fn read_head(stream: &mut TcpStream) -> String {
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap_or(0) == 1 {
+        head.push(byte[0]);
+    }
+    String::from_utf8_lossy(&head).into_owned()
+}
+
+/// Runs a fake HTTP proxy answering every request with `body`, and returns its
+/// URL and the request lines it saw. `CONNECT` tunnels are accepted, and the
+/// tunnelled request is answered the same way.
+fn serve_proxy(body: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let url = format!("http://{}", listener.local_addr().expect("Failed"));
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let line = read_head(&mut stream)
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+
+            if line.starts_with("CONNECT") {
+                let _ = stream.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n");
+                read_head(&mut stream);
+            }
+            log.lock().expect("Failed").push(line);
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    (url, seen)
+}
+
+/// Serves `body` directly, without a proxy, and returns the port.
+fn serve_direct(body: &'static str) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let port = listener.local_addr().expect("Failed").port();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            read_head(&mut stream);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    port
+}
+
+#[test]
+fn test_download_through_proxy() {
+    let dest = PathBuf::from("/tmp/test_download_proxy");
+    let _ = fs::remove_dir_all(&dest);
+    for var in ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY", "NO_PROXY"] {
+        unsafe {
+            env::remove_var(var);
+            env::remove_var(var.to_lowercase());
+        }
+    }
+
+    let (proxy, seen) = serve_proxy("via proxy");
+    let options = DownloadOptions {
+        proxy: Some(proxy.clone()),
+        attempts: Some(1),
+        ..Default::default()
+    };
+    download_file_with(
+        "http://mirror.invalid/a.txt",
+        dest.clone(),
+        "a.txt",
+        &options,
+    )
+    .expect("Explicit proxy was not used");
+    assert_eq!(
+        fs::read_to_string(dest.join("a.txt")).expect("Failed"),
+        "via proxy"
+    );
+    assert_eq!(seen.lock().expect("Failed").len(), 1);
+
+    unsafe {
+        env::set_var("http_proxy", &proxy);
+    }
+    download_file("http://mirror.invalid/b.txt", dest.clone(), "b.txt")
+        .expect("HTTP_PROXY was not honored");
+    assert_eq!(seen.lock().expect("Failed").len(), 2);
+
+    let port = serve_direct("direct");
+    unsafe {
+        env::set_var("NO_PROXY", "example.org, LOCALHOST");
+    }
+    download_file(
+        &format!("http://localhost:{port}/c.txt"),
+        dest.clone(),
+        "c.txt",
+    )
+    .expect("NO_PROXY host failed");
+    assert_eq!(
+        fs::read_to_string(dest.join("c.txt")).expect("Failed"),
+        "direct"
+    );
+    assert_eq!(
+        seen.lock().expect("Failed").len(),
+        2,
+        "NO_PROXY host was proxied"
+    );
+
+    unsafe {
+        env::set_var("NO_PROXY", ".invalid");
+    }
+    let direct = DownloadOptions {
+        attempts: Some(1),
+        ..Default::default()
+    };
+    assert!(download_file_with(
+        "http://mirror.invalid/d.txt",
+        dest.clone(),
+        "d.txt",
+        &direct
+    )
+    .is_err());
+    assert_eq!(
+        seen.lock().expect("Failed").len(),
+        2,
+        "Subdomain match failed"
+    );
+
+    let invalid = DownloadOptions {
+        proxy: Some("::nao e uma url::".into()),
+        ..Default::default()
+    };
+    assert!(download_file_with(
+        "http://mirror.invalid/e.txt",
+        dest.clone(),
+        "e.txt",
+        &invalid
+    )
+    .is_err());
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Download Através de Proxy Passou!\x1b[0m");
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "test-util")]
+
+use sandbox_utils::*;
+use std::path::PathBuf;
+
+fn config(name: &str, arch: &str) -> SandboxConfig {
+    let base = PathBuf::from("/tmp/test_reset").join(name);
+    SandboxConfig {
+        app_name: name.into(),
+        app_arch: arch.into(),
+        safe_home: base.join("home"),
+        config_dir: base.join("config"),
+        config_file: base.join("config/config.toml"),
+        default_cache: base.join("cache"),
+        default_rootfs: base.join("rootfs"),
+        temp_cache: base.join("tmp"),
+    }
+}
+
+#[test]
+fn test_reset_global_state() {
+    sandbox_init_with(config("primeiro", "x86_64")).expect("Init failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+    sandbox_init_with(config("segundo", "aarch64")).expect("Init failed");
+    assert_eq!(
+        app_name(),
+        "primeiro",
+        "Without a reset the first init wins"
+    );
+
+    reset_config();
+    sandbox_init_with(config("segundo", "aarch64")).expect("Init failed");
+    assert_eq!(app_name(), "segundo");
+    assert_eq!(app_arch(), "aarch64");
+
+    reset_tool();
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+    assert_eq!(sandbox_tool(), USE_BWRAP);
+
+    reset_config();
+    reset_tool();
+    std::fs::remove_dir_all("/tmp/test_reset").expect("Failed");
+    println!("\x1b[1;32m--> Reinício do Estado Global Passou!\x1b[0m");
+}
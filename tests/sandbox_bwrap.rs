@@ -1,6 +1,6 @@
 use sandbox_utils::*;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub fn test_file(name: &str) -> PathBuf {
     let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -19,7 +19,7 @@ fn test1_target_not_found() {
         ..Default::default()
     };
 
-    let _ = SandBox::run(config).map_err(|e| {
+    let _ = SandBox::run(config).inspect_err(|e| {
         if let Some(err) = e.downcast_ref::<RootfsNotFoundError>() {
             match failed_exist_rootfs(&format!("{} setup", app_name()), &err.0.to_string_lossy()) {
                 Ok(_) => {}
@@ -28,7 +28,6 @@ fn test1_target_not_found() {
                 }
             }
         }
-        e
     });
 }
 
@@ -59,3 +58,568 @@ fn test2_run_command_bwrap() {
     SandBox::run(config.clone()).expect("Failed");
     fs::remove_dir_all(dest).expect("Failed");
 }
+
+#[test]
+fn test3_fix_mtab_directory() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_mtab_dir");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    let mtab = dest.join("rootfs/etc/mtab");
+    let _ = fs::remove_file(&mtab);
+    fs::create_dir_all(mtab.join("nested")).expect("Failed to create mtab directory");
+
+    SandBox::run(SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "true".to_string(),
+        ..Default::default()
+    })
+    .expect("Failed");
+
+    let md = fs::symlink_metadata(&mtab).expect("mtab is missing");
+    assert!(
+        md.is_symlink(),
+        "etc/mtab should have been replaced by a symlink"
+    );
+    assert_eq!(
+        fs::read_link(&mtab).expect("Failed"),
+        PathBuf::from("/proc/self/mounts")
+    );
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test4_trailing_slash_rootfs() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_trailing_slash");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let _ = fs::remove_file(dest.join("rootfs/etc/mtab"));
+
+    SandBox::run(SandBoxConfig {
+        rootfs: PathBuf::from("/tmp//test_trailing_slash/"),
+        run_cmd: "true".to_string(),
+        ..Default::default()
+    })
+    .expect("Run with a trailing-slash rootfs failed");
+
+    let mtab = dest.join("rootfs/etc/mtab");
+    assert!(fs::symlink_metadata(&mtab).is_ok_and(|md| md.is_symlink()));
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test5_raw_tool_args() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_raw_args");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    SandBox::run(SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "true".to_string(),
+        raw_tool_args: vec!["--setenv".into(), "RAW_ARG".into(), "with space".into()],
+        ..Default::default()
+    })
+    .expect("Failed");
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test6_custom_hostname() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_hostname");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "true".to_string(),
+        hostname: Some("caixa de areia".to_string()),
+        ..Default::default()
+    };
+    assert!(
+        config.validate().is_err(),
+        "Hostnames with spaces must be rejected"
+    );
+
+    let config = SandBoxConfig {
+        hostname: Some("caixa".to_string()),
+        ..config
+    };
+    SandBox::run(config.clone()).expect("Failed");
+
+    let err = SandBoxConfig {
+        rootfs_tool: USE_PROOT.to_string(),
+        ..config
+    }
+    .validate()
+    .expect_err("PRoot cannot set the hostname");
+    assert!(err.to_string().contains("hostname"), "{err}");
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test7_kernel_overlay() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_bwrap_overlay");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    let overlay = OverlayConfig {
+        upper: dest.join("upper"),
+        work: dest.join("work"),
+    };
+
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "true".to_string(),
+        overlay: Some(overlay.clone()),
+        ..Default::default()
+    };
+
+    let err = SandBoxConfig {
+        rootfs_tool: USE_PROOT.to_string(),
+        ..config.clone()
+    }
+    .validate()
+    .expect_err("PRoot must reject the kernel overlay");
+    assert!(err.downcast_ref::<UnsupportedOptionError>().is_some());
+
+    SandBox::run(config).expect("Failed");
+    assert!(overlay.upper.is_dir() && overlay.work.is_dir());
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test8_device_nodes() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_devices");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "true".to_string(),
+        devices: vec![
+            PathBuf::from("/dev/null"),
+            PathBuf::from("/dev/inexistente"),
+        ],
+        ..Default::default()
+    };
+    SandBox::run(config.clone()).expect("Missing devices must only warn");
+
+    for bad in ["/etc/passwd", "/dev/../etc/passwd"] {
+        let invalid = SandBoxConfig {
+            devices: vec![PathBuf::from(bad)],
+            ..config.clone()
+        };
+        assert!(invalid.validate().is_err(), "{bad} must be rejected");
+    }
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test9_private_pseudo_fs() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_pseudo_fs_bwrap");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    for pseudo_fs in [PseudoFs::Host, PseudoFs::Private] {
+        SandBox::run(SandBoxConfig {
+            rootfs: dest.clone(),
+            run_cmd: "test -d /proc".to_string(),
+            pseudo_fs,
+            ..Default::default()
+        })
+        .expect("Failed");
+    }
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test10_command_string() {
+    use std::process::Command;
+
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let config = SandBoxConfig {
+        rootfs: PathBuf::from("/tmp/test_command_string"),
+        run_cmd: "echo 'olá mundo' $HOME".to_string(),
+        hostname: Some("caixa".to_string()),
+        use_root: true,
+        ..Default::default()
+    };
+
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    assert_eq!(argv[0], tool_target().to_string_lossy());
+    assert_eq!(argv[1..3], ["--unshare-user", "--share-net"]);
+    assert!(argv.windows(2).any(|w| w == ["--hostname", "caixa"]));
+    assert!(argv.iter().any(|a| a == "PS1=# "));
+    assert_eq!(
+        argv[argv.len() - 3..],
+        ["/bin/sh", "-c", config.run_cmd.as_str()]
+    );
+
+    let line = SandBox::command_string(&config).expect("Failed");
+    let out = Command::new("/bin/sh")
+        .args(["-c", &format!("printf '%s\\0' {line}")])
+        .output()
+        .expect("Failed to run sh");
+    let parsed: Vec<String> = String::from_utf8(out.stdout)
+        .expect("Invalid UTF-8")
+        .split_terminator('\0')
+        .map(String::from)
+        .collect();
+    assert_eq!(parsed, argv);
+    println!("\x1b[1;32m--> Linha de Comando do Sandbox Passou!\x1b[0m");
+}
+
+#[test]
+fn test11_dry_run() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_dry_run");
+    let _ = fs::remove_dir_all(&dest);
+    let marker = dest.join("executado");
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: format!("touch {}", marker.display()),
+        hostname: Some("caixa".to_string()),
+        dry_run: true,
+        ..Default::default()
+    };
+
+    let status = SandBox::run(config.clone()).expect("Dry run must not need a rootfs");
+    assert!(status.success());
+
+    // Runs of concurrent tests may take the next hostname dir in between, so
+    // its number is left out of the comparison.
+    let prefix = format!("hostname-{}-", std::process::id());
+    let without_run = |line: &str| -> String {
+        let mut parts = line.split(&prefix);
+        let first = parts.next().unwrap_or_default().to_string();
+        parts.fold(first, |acc, part| {
+            acc + &prefix + part.trim_start_matches(|c: char| c.is_ascii_digit())
+        })
+    };
+    let output = SandBox::run_captured(config.clone()).expect("Failed");
+    let line = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(line.contains(&prefix), "{line}");
+    assert_eq!(
+        without_run(line.trim_end()),
+        without_run(&SandBox::command_string(&config).expect("Failed"))
+    );
+    assert!(line.contains("'PS1=$ '") && line.contains("caixa"));
+    assert!(!dest.exists(), "Dry run must not touch the filesystem");
+    println!("\x1b[1;32m--> Execução Simulada Passou!\x1b[0m");
+}
+
+#[test]
+fn test12_share_net() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_share_net_bwrap");
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "true".to_string(),
+        ..Default::default()
+    };
+
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    assert!(argv.iter().any(|a| a == "--share-net"));
+    assert!(!argv.iter().any(|a| a == "--unshare-net"));
+
+    let offline = SandBoxConfig {
+        share_net: false,
+        ..config
+    };
+    let argv = SandBox::command_argv(&offline).expect("Failed");
+    assert!(argv.iter().any(|a| a == "--unshare-net"));
+    assert!(!argv.iter().any(|a| a == "--share-net"));
+
+    let proot = SandBoxConfig {
+        rootfs_tool: USE_PROOT.to_string(),
+        ..offline
+    };
+    let err = proot
+        .validate()
+        .expect_err("PRoot cannot isolate the network");
+    assert!(err.to_string().contains("share_net"), "{err}");
+    println!("\x1b[1;32m--> Rede Isolada Passou!\x1b[0m");
+}
+
+#[test]
+fn test13_readonly_rootfs() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let config = SandBoxConfig {
+        rootfs: PathBuf::from("/tmp/test_readonly_bwrap"),
+        run_cmd: "true".to_string(),
+        readonly_rootfs: true,
+        raw_tool_args: vec!["--tmpfs".to_string(), "/scratch".to_string()],
+        ..Default::default()
+    };
+
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    let remount = argv
+        .windows(2)
+        .position(|w| w == ["--remount-ro", "/"])
+        .expect("Missing --remount-ro");
+    let env = argv.iter().position(|a| a == "env").expect("Missing env");
+    assert!(argv.iter().position(|a| a == "/scratch") < Some(remount));
+    assert!(remount < env);
+
+    let proot = SandBoxConfig {
+        rootfs_tool: USE_PROOT.to_string(),
+        ..config
+    };
+    assert!(proot.validate().is_err());
+    println!("\x1b[1;32m--> RootFS Somente Leitura Passou!\x1b[0m");
+}
+
+#[test]
+fn test14_bind_display() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let runtime = PathBuf::from("/tmp/test_bind_display_runtime");
+    fs::create_dir_all(&runtime).expect("Failed");
+    fs::write(runtime.join("wayland-9"), b"").expect("Failed");
+    unsafe {
+        std::env::set_var("XDG_RUNTIME_DIR", &runtime);
+        std::env::set_var("WAYLAND_DISPLAY", "wayland-9");
+        std::env::set_var("DISPLAY", ":9");
+    }
+
+    let config = SandBoxConfig {
+        rootfs: PathBuf::from("/tmp/test_bind_display"),
+        run_cmd: "true".to_string(),
+        bind_display: true,
+        ..Default::default()
+    };
+
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    let socket = "/tmp/test_bind_display_runtime/wayland-9";
+    assert!(argv.windows(3).any(|w| w == ["--bind-try", socket, socket]));
+    assert!(argv
+        .windows(3)
+        .any(|w| w == ["--setenv", "WAYLAND_DISPLAY", "wayland-9"]));
+    assert!(argv.contains(&"XDG_RUNTIME_DIR=/tmp/test_bind_display_runtime".to_string()));
+
+    let x11 = Path::new("/tmp/.X11-unix").is_dir();
+    let x11_bind = ["--ro-bind-try", "/tmp/.X11-unix", "/tmp/.X11-unix"];
+    assert_eq!(argv.windows(3).any(|w| w == x11_bind), x11);
+    assert_eq!(argv.contains(&"DISPLAY=:9".to_string()), x11);
+
+    let argv = SandBox::command_argv(&SandBoxConfig {
+        bind_display: false,
+        ..config
+    })
+    .expect("Failed");
+    assert!(!argv.iter().any(|a| a.contains("wayland-9")));
+    fs::remove_dir_all(runtime).expect("Failed");
+    println!("\x1b[1;32m--> Servidor Gráfico Passou!\x1b[0m");
+}
+
+#[test]
+fn test15_seccomp_fd() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_seccomp_bwrap");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let program = dest.join("filter.bpf");
+    fs::write(&program, [0u8; 8]).expect("Failed");
+
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "readlink /proc/$$/fd/10".to_string(),
+        seccomp: Some(program.clone()),
+        ..Default::default()
+    };
+
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    assert!(argv.windows(2).any(|w| w == ["--seccomp", "10"]));
+
+    // The test bwrap execs the guest directly, so the inherited fd is visible.
+    let output = SandBox::run_captured(config.clone()).expect("Failed");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        program.display().to_string()
+    );
+
+    let err = SandBox::run(SandBoxConfig {
+        seccomp: Some(dest.join("missing.bpf")),
+        ..config
+    })
+    .expect_err("A missing seccomp program must be rejected");
+    assert!(err.to_string().contains("missing.bpf"), "{err}");
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Descritor Seccomp Passou!\x1b[0m");
+}
+
+#[test]
+fn test16_concurrent_hostname_dirs() {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_concurrent_hostname");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let release = dest.join("liberar");
+    let _ = fs::remove_file(&release);
+
+    // Other tests may run sandboxes at the same time, and earlier processes
+    // may have left directories behind, so only this test's are looked at.
+    let prefix = format!("hostname-{}-", std::process::id());
+    let written_names = || -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(temp_cache())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+            .filter_map(|e| fs::read_to_string(e.path().join("hostname")).ok())
+            .filter(|name| name.starts_with("concorrente"))
+            .collect();
+        names.sort();
+        names
+    };
+
+    // The test bwrap runs the guest command on the host, so both runs stay
+    // alive until the release file is created.
+    let runs: Vec<_> = ["concorrente1", "concorrente2"]
+        .map(|name| {
+            let config = SandBoxConfig {
+                rootfs: dest.clone(),
+                run_cmd: format!("while [ ! -e {} ]; do sleep 0.05; done", release.display()),
+                hostname: Some(name.to_string()),
+                ..Default::default()
+            };
+            thread::spawn(move || SandBox::run(config).map_err(|e| e.to_string()))
+        })
+        .into();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while written_names().len() < 2 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(20));
+    }
+    let names = written_names();
+    fs::write(&release, "").expect("Failed to release the runs");
+
+    for run in runs {
+        run.join().expect("Run panicked").expect("Failed");
+    }
+    assert_eq!(names, ["concorrente1\n", "concorrente2\n"]);
+    assert!(written_names().is_empty(), "Hostname dirs must be removed");
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Hostnames Concorrentes Passou!\x1b[0m");
+}
+
+#[test]
+fn test17_concurrent_fix_mtab() {
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_concurrent_mtab");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    let etc = dest.join("rootfs/etc");
+    for round in 0..5 {
+        let _ = fs::remove_file(etc.join("mtab"));
+        if round % 2 == 0 {
+            fs::create_dir_all(etc.join("mtab/nested")).expect("Failed to create mtab directory");
+        }
+
+        let barrier = Arc::new(Barrier::new(4));
+        let runs: Vec<_> = (0..4)
+            .map(|_| {
+                let barrier = barrier.clone();
+                let config = SandBoxConfig {
+                    rootfs: dest.clone(),
+                    run_cmd: "true".to_string(),
+                    ..Default::default()
+                };
+                thread::spawn(move || {
+                    barrier.wait();
+                    SandBox::run(config).map_err(|e| e.to_string())
+                })
+            })
+            .collect();
+
+        for run in runs {
+            run.join()
+                .expect("Run panicked")
+                .expect("A concurrent mtab fix must not fail the run");
+        }
+        assert_eq!(
+            fs::read_link(etc.join("mtab")).expect("mtab is not a symlink"),
+            PathBuf::from("/proc/self/mounts")
+        );
+    }
+
+    let leftovers: Vec<_> = fs::read_dir(&etc)
+        .expect("Failed")
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with(".mtab."))
+        .collect();
+    assert!(
+        leftovers.is_empty(),
+        "Temporary mtab links left: {leftovers:?}"
+    );
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Correcao Concorrente do Mtab Passou!\x1b[0m");
+}
+
+#[test]
+fn test18_hostname_dir_removed_on_early_error() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_BWRAP).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_hostname_early_error");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    // The missing seccomp program fails the run after the hostname files
+    // were written.
+    SandBox::run(SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "true".to_string(),
+        hostname: Some("vazamento".to_string()),
+        seccomp: Some(dest.join("missing.bpf")),
+        ..Default::default()
+    })
+    .expect_err("A missing seccomp program must be rejected");
+
+    let prefix = format!("hostname-{}-", std::process::id());
+    let leaked = fs::read_dir(temp_cache())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+        .filter_map(|e| fs::read_to_string(e.path().join("hostname")).ok())
+        .any(|name| name == "vazamento\n");
+    assert!(!leaked, "Hostname dir left behind after a failed run");
+
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Hostname Removido Apos Erro Passou!\x1b[0m");
+}
@@ -1,7 +1,7 @@
 use sandbox_utils::*;
 use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub fn test_file(name: &str) -> PathBuf {
     let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -32,6 +32,7 @@ fn test1_sandbox_output() {
     println!("Config File:   {:?}", config_file());
     println!("Cache Dir:     {:?}", default_cache());
     println!("Rootfs Dir:    {:?}", default_rootfs());
+    println!("User Rootfs:   {:?}", resolved_rootfs());
     println!("Temp Cache:    {:?}", temp_cache());
     println!("\x1b[1;34m{}\x1b[0m", "-".repeat(50));
     newfn();
@@ -101,3 +102,744 @@ fn test3_messages_dialog() {
     let diff = get_config_diff(&old, &new);
     render_table(diff);
 }
+
+#[test]
+fn test4_copy_net_files() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_net_files");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    SandBox::run(SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "cat /etc/hosts".to_string(),
+        copy_net_files: true,
+        ..Default::default()
+    })
+    .expect("Failed");
+
+    let guest_hosts = dest.join("rootfs/etc/hosts");
+    assert!(!fs::symlink_metadata(&guest_hosts)
+        .expect("Failed")
+        .is_symlink());
+    if let Ok(host) = fs::read("/etc/hosts") {
+        assert_eq!(fs::read(&guest_hosts).expect("Failed"), host);
+    }
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test5_per_user_rootfs() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    assert_eq!(resolved_rootfs(), default_rootfs());
+
+    set_per_user_rootfs(true);
+    let per_user = resolved_rootfs();
+    set_per_user_rootfs(false);
+
+    println!("Per-user Rootfs: {:?}", per_user);
+    assert_eq!(per_user.parent(), default_rootfs().parent());
+    assert!(per_user
+        .to_string_lossy()
+        .ends_with(&format!(".ArchLinux-{}", host_uid())));
+}
+
+#[test]
+fn test6_validate_unknown_tool() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let config = SandBoxConfig {
+        rootfs: PathBuf::from("/tmp/pasta_inexistente"),
+        rootfs_tool: "docker".to_string(),
+        ..Default::default()
+    };
+
+    assert!(config.validate().is_err());
+    let err = SandBox::run(config).expect_err("Unknown tool must be rejected");
+    assert!(err.downcast_ref::<RootfsNotFoundError>().is_none());
+    assert!(err.to_string().contains("docker"));
+}
+
+#[test]
+fn test7_fix_mtab_proot() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_mtab_proot");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let mtab = dest.join("rootfs/etc/mtab");
+    let _ = fs::remove_file(&mtab);
+
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "true".to_string(),
+        fix_mtab: false,
+        ..Default::default()
+    };
+    SandBox::run(config.clone()).expect("Failed");
+    assert!(
+        fs::symlink_metadata(&mtab).is_err(),
+        "fix_mtab = false must leave the rootfs untouched"
+    );
+
+    SandBox::run(SandBoxConfig {
+        fix_mtab: true,
+        ..config.clone()
+    })
+    .expect("Failed");
+    assert_eq!(
+        fs::read_link(&mtab).expect("mtab symlink is missing"),
+        PathBuf::from("/proc/self/mounts")
+    );
+
+    let config = SandBoxConfig {
+        fix_mtab: true,
+        mtab_target: Some(PathBuf::from("/proc/mounts")),
+        ..config
+    };
+    SandBox::run(config.clone()).expect("Failed");
+    assert_eq!(
+        fs::read_link(&mtab).expect("mtab symlink is missing"),
+        PathBuf::from("/proc/mounts")
+    );
+
+    let etc = dest.join("rootfs/etc");
+    fs::rename(&etc, dest.join("etc.bak")).expect("Failed");
+    let err = SandBox::run(config).expect_err("A failed mtab fix must be reported");
+    assert!(err.to_string().contains("mtab"), "{err}");
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test8_resolve_tool() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+
+    let resolved = resolve_tool(USE_PROOT).expect("proot should be resolvable");
+    println!("Resolved proot: {:?}", resolved);
+    assert!(resolved.ends_with(USE_PROOT));
+
+    assert!(resolve_tool("ferramenta_inexistente").is_err());
+}
+
+#[test]
+fn test9_custom_prompt() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_prompt");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let out = dest.join("ps1.txt");
+
+    SandBox::run(SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: format!("printf %s \"$PS1\" > {}", out.display()),
+        prompt: Some("(sandbox|teste) $ ".to_string()),
+        ..Default::default()
+    })
+    .expect("Failed");
+
+    assert_eq!(
+        fs::read_to_string(&out).expect("Failed"),
+        "(sandbox|teste) $ "
+    );
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test10_extra_binds_report() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let config = SandBoxConfig::default();
+    let report = SandBox::extra_binds(&config);
+    println!(
+        "Bound {} optional paths, skipped {:?}",
+        report.applied.len(),
+        report.skipped
+    );
+    assert!(report.applied.iter().all(|p| PathBuf::from(p).exists()));
+    assert!(report.skipped.iter().all(|p| !PathBuf::from(p).exists()));
+
+    let disabled = SandBox::extra_binds(&SandBoxConfig {
+        ignore_extra_bind: true,
+        ..config
+    });
+    assert!(disabled.applied.is_empty());
+    assert!(!disabled.skipped.is_empty());
+}
+
+#[test]
+fn test11_missing_bind_source() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_missing_bind");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    SandBox::run(SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "true".to_string(),
+        args_bind: "--bind=/caminho/inexistente:/opt -b /tmp".to_string(),
+        ..Default::default()
+    })
+    .expect("A missing bind source must only warn");
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test12_private_pseudo_fs() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_pseudo_fs_proot");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    for pseudo_fs in [PseudoFs::Host, PseudoFs::Private] {
+        SandBox::run(SandBoxConfig {
+            rootfs: dest.clone(),
+            run_cmd: "test -d /proc".to_string(),
+            pseudo_fs,
+            ..Default::default()
+        })
+        .expect("Failed");
+    }
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test13_timeout_kills_group() {
+    use std::time::{Duration, Instant};
+
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_timeout_proot");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let pid_file = dest.join("child.pid");
+
+    let started = Instant::now();
+    let err = SandBox::run(SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: format!("sleep 60 & echo $! > {}; wait", pid_file.display()),
+        timeout: Some(Duration::from_millis(500)),
+        ..Default::default()
+    })
+    .expect_err("A hung guest must time out");
+
+    assert!(err.downcast_ref::<TimedOutError>().is_some(), "{err}");
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    let pid = fs::read_to_string(&pid_file).expect("Missing pid file");
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+    assert!(
+        stat.is_empty() || stat.contains(") Z "),
+        "Background guest process survived the timeout: {stat}"
+    );
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Timeout do Sandbox Passou!\x1b[0m");
+}
+
+#[test]
+fn test14_can_use() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+
+    let proot = can_use(USE_PROOT);
+    println!("{:?}", proot);
+    assert_eq!(proot.tool, USE_PROOT);
+    assert_eq!(proot.user_namespaces, None);
+    assert!(proot.usable());
+
+    let bwrap = can_use(USE_BWRAP);
+    println!("{:?}", bwrap);
+    assert!(bwrap.user_namespaces.is_some());
+
+    let unknown = can_use("ferramenta-inexistente");
+    assert!(unknown.installed.is_none() && !unknown.downloadable);
+    assert!(!unknown.usable());
+    println!("\x1b[1;32m--> Disponibilidade das Ferramentas Passou!\x1b[0m");
+}
+
+#[test]
+fn test15_custom_user() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_user");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let out = dest.join("user.txt");
+
+    for (use_root, uid) in [(false, host_uid()), (true, 0)] {
+        SandBox::run(SandBoxConfig {
+            rootfs: dest.clone(),
+            run_cmd: format!("printf %s \"$USER:$LOGNAME:$UID\" > {}", out.display()),
+            user: Some("builder".to_string()),
+            use_root,
+            ..Default::default()
+        })
+        .expect("Failed");
+
+        assert_eq!(
+            fs::read_to_string(&out).expect("Failed"),
+            format!("builder:builder:{uid}")
+        );
+    }
+    fs::remove_dir_all(dest).expect("Failed");
+}
+
+#[test]
+fn test16_exit_status() {
+    use std::os::unix::process::ExitStatusExt;
+
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_exit_status");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        ..Default::default()
+    };
+
+    let ok = SandBox::run(SandBoxConfig {
+        run_cmd: "true".to_string(),
+        ..config.clone()
+    })
+    .expect("Failed");
+    assert!(ok.success());
+
+    let failed = SandBox::run(SandBoxConfig {
+        run_cmd: "exit 3".to_string(),
+        ..config.clone()
+    })
+    .expect("A failing guest command is not a run error");
+    assert_eq!(failed.code(), Some(3));
+
+    let killed = SandBox::run(SandBoxConfig {
+        run_cmd: "kill -9 $$".to_string(),
+        ..config.clone()
+    })
+    .expect("A killed guest command is not a run error");
+    assert!(killed.signal() == Some(9) || killed.code() == Some(137));
+
+    let missing_tool = SandBox::run(SandBoxConfig {
+        run_cmd: "true".to_string(),
+        tool_target: PathBuf::from("/inexistente/proot"),
+        ..config
+    });
+    assert!(
+        missing_tool.is_err(),
+        "A tool that cannot spawn must be an error"
+    );
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Código de Saída do Sandbox Passou!\x1b[0m");
+}
+
+#[test]
+fn test17_run_captured() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_run_captured");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    let output = SandBox::run_captured(SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "yes saida | head -c 1000000; yes erro | head -c 500000 >&2; exit 4".to_string(),
+        ..Default::default()
+    })
+    .expect("Failed");
+
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(output.stdout.len(), 1_000_000);
+    assert_eq!(output.stderr.len(), 500_000);
+    assert!(output.stdout.starts_with(b"saida\n"));
+    assert!(output.stderr.starts_with(b"erro\n"));
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Captura da Saída Passou!\x1b[0m");
+}
+
+#[test]
+fn test18_custom_env() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_custom_env");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    let env = vec![
+        ("LANG".to_string(), "C.UTF-8".to_string()),
+        ("FOO".to_string(), "a b|c 'd' $e".to_string()),
+        ("SHELL".to_string(), "/bin/ash".to_string()),
+    ];
+    let output = SandBox::run_captured(SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "printf '%s\\n' \"$LANG\" \"$FOO\" \"$SHELL\"".to_string(),
+        env: env.clone(),
+        ..Default::default()
+    })
+    .expect("Failed");
+    assert_eq!(
+        String::from_utf8(output.stdout).expect("Invalid UTF-8"),
+        "C.UTF-8\na b|c 'd' $e\n/bin/ash\n"
+    );
+
+    for key in ["", "A=B"] {
+        let invalid = SandBoxConfig {
+            env: vec![(key.to_string(), "x".to_string())],
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err(), "{key:?} must be rejected");
+    }
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Variáveis de Ambiente Passou!\x1b[0m");
+}
+
+#[test]
+fn test19_workdir() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_workdir");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "true".to_string(),
+        workdir: Some(PathBuf::from("/etc")),
+        ..Default::default()
+    };
+
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    assert!(argv.windows(2).any(|w| w == ["-w", "/etc"]));
+    SandBox::run(config.clone()).expect("Existing workdir must be accepted");
+
+    let err = SandBox::run(SandBoxConfig {
+        workdir: Some(PathBuf::from("/projeto/inexistente")),
+        ..config.clone()
+    })
+    .expect_err("Missing workdir must be rejected");
+    assert!(err.to_string().contains("/projeto/inexistente"), "{err}");
+
+    let relative = SandBoxConfig {
+        workdir: Some(PathBuf::from("etc")),
+        ..config
+    };
+    assert!(relative.validate().is_err());
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Diretório de Trabalho Passou!\x1b[0m");
+}
+
+#[test]
+fn test20_custom_shell() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_custom_shell");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "echo $SHELL".to_string(),
+        shell: Some("/bin/ash".to_string()),
+        ..Default::default()
+    };
+
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    assert!(argv.iter().any(|a| a == "SHELL=/bin/ash"));
+    assert!(argv.windows(2).any(|w| w == ["/bin/ash", "-c"]));
+
+    let output = SandBox::run_captured(SandBoxConfig {
+        shell: None,
+        ..config.clone()
+    })
+    .expect("Failed");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "/bin/sh\n");
+
+    let err = SandBox::run(SandBoxConfig {
+        shell: Some("/bin/zsh".to_string()),
+        ..config.clone()
+    })
+    .expect_err("Missing shell must be rejected");
+    assert!(err.to_string().contains("/bin/zsh"), "{err}");
+    let missing = err
+        .downcast_ref::<ShellNotFoundError>()
+        .expect("Expected ShellNotFoundError");
+    assert_eq!(missing.0, dest.join("rootfs/bin/zsh"));
+
+    let script = dest.join("rootfs/bin/not-executable");
+    fs::write(&script, "#!/bin/sh\n").expect("Failed");
+    let err = SandBox::run(SandBoxConfig {
+        shell: Some("/bin/not-executable".to_string()),
+        ..config
+    })
+    .expect_err("A shell without the exec bit must be rejected");
+    assert!(err.downcast_ref::<ShellNotFoundError>().is_some(), "{err}");
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Shell Personalizado Passou!\x1b[0m");
+}
+
+#[test]
+fn test21_run_argv() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_run_argv");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_argv: Some(
+            ["printf", "%s|", "script with spaces.py", "$HOME 'x' \"y\""]
+                .map(String::from)
+                .to_vec(),
+        ),
+        ..Default::default()
+    };
+
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    assert!(!argv.iter().any(|a| a == "-c"));
+    assert_eq!(argv.last().map(String::as_str), Some("$HOME 'x' \"y\""));
+
+    let output = SandBox::run_captured(config.clone()).expect("Failed");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "script with spaces.py|$HOME 'x' \"y\"|"
+    );
+
+    let both = SandBoxConfig {
+        run_cmd: "true".to_string(),
+        ..config.clone()
+    };
+    assert!(both.validate().is_err());
+    let empty = SandBoxConfig {
+        run_argv: Some(Vec::new()),
+        ..config
+    };
+    assert!(empty.validate().is_err());
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Argumentos Diretos Passou!\x1b[0m");
+}
+
+#[test]
+fn test22_bind_gpu() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let config = SandBoxConfig {
+        rootfs: PathBuf::from("/tmp/test_bind_gpu"),
+        run_cmd: "true".to_string(),
+        ignore_extra_bind: true,
+        ..Default::default()
+    };
+    let gpu = "--bind=/dev/dri:/dev/dri".to_string();
+
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    assert!(!argv.contains(&gpu));
+
+    let argv = SandBox::command_argv(&SandBoxConfig {
+        bind_gpu: true,
+        ..config
+    })
+    .expect("Failed");
+    assert_eq!(argv.contains(&gpu), Path::new("/dev/dri").exists());
+    println!("\x1b[1;32m--> Dispositivos de GPU Passou!\x1b[0m");
+}
+
+#[test]
+fn test23_bind_audio() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let runtime = PathBuf::from("/tmp/test_bind_audio_runtime");
+    fs::create_dir_all(runtime.join("pulse")).expect("Failed");
+    fs::write(runtime.join("pulse/native"), b"").expect("Failed");
+    unsafe {
+        std::env::set_var("XDG_RUNTIME_DIR", &runtime);
+    }
+
+    let config = SandBoxConfig {
+        rootfs: PathBuf::from("/tmp/test_bind_audio"),
+        run_cmd: "true".to_string(),
+        bind_audio: true,
+        ..Default::default()
+    };
+
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    let pulse = "/tmp/test_bind_audio_runtime/pulse/native";
+    assert!(argv.contains(&format!("--bind={pulse}:{pulse}")));
+    assert!(argv.contains(&format!("PULSE_SERVER=unix:{pulse}")));
+    assert!(argv.contains(&"XDG_RUNTIME_DIR=/tmp/test_bind_audio_runtime".to_string()));
+    assert!(!argv.iter().any(|a| a.contains("pipewire-0")));
+
+    let argv = SandBox::command_argv(&SandBoxConfig {
+        bind_audio: false,
+        ..config
+    })
+    .expect("Failed");
+    assert!(!argv.iter().any(|a| a.contains("PULSE_SERVER")));
+    fs::remove_dir_all(runtime).expect("Failed");
+    println!("\x1b[1;32m--> Sockets de Áudio Passou!\x1b[0m");
+}
+
+#[test]
+fn test24_timeout_reaps_orphans() {
+    use std::time::Duration;
+
+    unsafe extern "C" {
+        /// Marks the test process as a subreaper, like an init inside a container.
+        fn prctl(option: i32, arg2: u64, arg3: u64, arg4: u64, arg5: u64) -> i32;
+    }
+    const PR_SET_CHILD_SUBREAPER: i32 = 36;
+    assert_eq!(unsafe { prctl(PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) }, 0);
+
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_timeout_reap_proot");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let pid_file = dest.join("child.pid");
+
+    let err = SandBox::run(SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: format!("sleep 60 & echo $! > {}; wait", pid_file.display()),
+        timeout: Some(Duration::from_millis(500)),
+        ..Default::default()
+    })
+    .expect_err("A hung guest must time out");
+    assert!(err.downcast_ref::<TimedOutError>().is_some(), "{err}");
+
+    let pid = fs::read_to_string(&pid_file).expect("Missing pid file");
+    let proc_dir = PathBuf::from(format!("/proc/{}", pid.trim()));
+    assert!(!proc_dir.exists(), "Orphaned guest process was not reaped");
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Processos Órfãos Coletados Passou!\x1b[0m");
+}
+
+#[test]
+fn test25_resource_limits() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_resource_limits");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "cat /proc/self/cgroup".to_string(),
+        memory_limit: Some(256 << 20),
+        cpu_quota: Some(0.5),
+        ..Default::default()
+    };
+
+    // Without a delegated cgroup v2 hierarchy, the run must fail up front.
+    match SandBox::run_captured(config.clone()) {
+        Ok(output) => {
+            let cgroups = String::from_utf8_lossy(&output.stdout);
+            assert!(cgroups.contains("sandbox_utils-"), "{cgroups}");
+        }
+        Err(err) => assert!(err.to_string().contains("cgroup v2"), "{err}"),
+    }
+
+    for invalid in [
+        SandBoxConfig {
+            memory_limit: Some(0),
+            ..config.clone()
+        },
+        SandBoxConfig {
+            cpu_quota: Some(f64::NAN),
+            ..config.clone()
+        },
+        SandBoxConfig {
+            cpu_quota: Some(-1.0),
+            ..config
+        },
+    ] {
+        assert!(invalid.validate().is_err());
+    }
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Limites de Recursos Passou!\x1b[0m");
+}
+
+#[test]
+fn test26_root_emulation() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_root_emulation");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+    let config = SandBoxConfig {
+        rootfs: dest.clone(),
+        run_cmd: "id -u".to_string(),
+        use_root: true,
+        raw_tool_args: vec!["--kill-on-exit".to_string()],
+        ..Default::default()
+    };
+
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    let position = |arg: &str| argv.iter().position(|a| a == arg);
+    assert!(position("-R") < position("-0"));
+    assert!(position("-0") < position("--kill-on-exit"));
+    assert!(position("--kill-on-exit") < position("env"));
+
+    let output = SandBox::run_captured(config).expect("Failed");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "0\n");
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Emulação de Root Passou!\x1b[0m");
+}
+
+#[test]
+fn test27_prompt_trailing_space() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let dest = PathBuf::from("/tmp/test_prompt_space");
+    extract_bootstrap(test_file("rootfs.tar.gz"), dest.clone()).expect("Failed to extract GZ");
+
+    for (use_root, prompt) in [(true, "# "), (false, "$ ")] {
+        let config = SandBoxConfig {
+            rootfs: dest.clone(),
+            run_cmd: "printf '%s|' \"$PS1\"".to_string(),
+            use_root,
+            ..Default::default()
+        };
+
+        let argv = SandBox::command_argv(&config).expect("Failed");
+        assert!(argv.contains(&format!("PS1={prompt}")));
+
+        let output = SandBox::run_captured(config).expect("Failed");
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("{prompt}|")
+        );
+    }
+    fs::remove_dir_all(dest).expect("Failed");
+    println!("\x1b[1;32m--> Prompt com Espaço Final Passou!\x1b[0m");
+}
+
+#[test]
+fn test28_secure_rootfs_hides_tmp_and_run() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+    set_sandbox_tool(USE_PROOT).expect("Failed");
+
+    let config = SandBoxConfig {
+        rootfs: PathBuf::from("/tmp/test_secure_pseudo_fs"),
+        secure_rootfs: true,
+        ..Default::default()
+    };
+    let argv = SandBox::command_argv(&config).expect("Failed");
+    assert!(argv.iter().any(|a| a == "-S"));
+    for hidden in ["/tmp", "/run"] {
+        assert!(
+            !argv
+                .iter()
+                .any(|a| a.starts_with(&format!("--bind={hidden}"))),
+            "{hidden} must not be bound under -S: {argv:?}"
+        );
+    }
+
+    let argv = SandBox::command_argv(&SandBoxConfig {
+        secure_rootfs: false,
+        ..config
+    })
+    .expect("Failed");
+    assert!(argv.iter().any(|a| a == "--bind=/tmp"));
+    println!("\x1b[1;32m--> Modo Seguro Sem /tmp e /run Passou!\x1b[0m");
+}
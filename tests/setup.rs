@@ -0,0 +1,70 @@
+use sandbox_utils::*;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::thread;
+
+pub fn test_file(name: &str) -> PathBuf {
+    let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    p.push("files");
+    p.push(name);
+    p
+}
+
+/// Serves `body` once over plain HTTP on a random local port and returns its URL.
+pub fn serve_once(body: Vec<u8>, path: &str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let url = format!("http://{}/{path}", listener.local_addr().expect("Failed"));
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("Failed to accept");
+        let mut request = [0u8; 4096];
+        let _ = stream.read(&mut request);
+        let header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        let _ = stream.write_all(header.as_bytes());
+        let _ = stream.write_all(&body);
+    });
+
+    url
+}
+
+#[test]
+#[cfg(feature = "gz")]
+fn test_ensure_rootfs() {
+    let base = PathBuf::from("/tmp/test_ensure_rootfs");
+    let _ = fs::remove_dir_all(&base);
+
+    sandbox_init_with(SandboxConfig {
+        app_name: "meuapp".into(),
+        app_arch: "x86_64".into(),
+        safe_home: base.join("home"),
+        config_dir: base.join("config"),
+        config_file: base.join("config/config.toml"),
+        default_cache: base.join("cache"),
+        default_rootfs: base.join("rootfs"),
+        temp_cache: base.join("tmp"),
+    })
+    .expect("Init failed");
+
+    let body = fs::read(test_file("rootfs.tar.gz")).expect("Missing fixture");
+    let url = serve_once(body, "download?arquivo=alpine");
+
+    let rootfs = ensure_rootfs(&url, Some(ArchiveFormat::Gz)).expect("Setup failed");
+    assert_eq!(rootfs, default_rootfs());
+    validate_rootfs(&rootfs).expect("Installed rootfs must be valid");
+    assert!(default_cache().join("rootfs.tar.gz").exists());
+    assert_eq!(
+        fs::read_link(rootfs.join("rootfs/etc/mtab")).expect("mtab was not fixed"),
+        PathBuf::from("/proc/self/mounts")
+    );
+
+    let again = ensure_rootfs("http://127.0.0.1:1/nunca", None).expect("Must be idempotent");
+    assert_eq!(again, rootfs);
+
+    fs::remove_dir_all(base).expect("Failed");
+    println!("\x1b[1;32m--> Preparação do Rootfs Passou!\x1b[0m");
+}
@@ -0,0 +1,52 @@
+use sandbox_utils::*;
+use std::process::Command;
+
+fn sh_echo(cmd: &str) -> String {
+    let out = Command::new("/bin/sh")
+        .args(["-c", &format!("printf '%s|' {cmd}")])
+        .output()
+        .expect("Failed to run sh");
+    String::from_utf8(out.stdout).expect("Invalid UTF-8")
+}
+
+#[test]
+fn test_shell_escape() {
+    assert_eq!(shell_escape("ls"), "ls");
+    assert_eq!(shell_escape("/usr/bin/env"), "/usr/bin/env");
+    assert_eq!(shell_escape(""), "''");
+    assert_eq!(shell_escape("a b"), "'a b'");
+    assert_eq!(shell_escape("it's"), "'it'\\''s'");
+    assert_eq!(shell_escape("$HOME"), "'$HOME'");
+    assert_eq!(shell_escape("FOO=bar"), "'FOO=bar'");
+}
+
+#[test]
+fn test_shell_join_assignment_is_command() {
+    let out = Command::new("/bin/sh")
+        .args(["-c", &shell_join(["FOO=bar"])])
+        .output()
+        .expect("Failed to run sh");
+    assert_eq!(
+        out.status.code(),
+        Some(127),
+        "FOO=bar must run as a command"
+    );
+}
+
+#[test]
+fn test_shell_join_roundtrip() {
+    let args = [
+        "echo",
+        "com espaço",
+        "aspas 'simples'",
+        "\"duplas\"",
+        "$HOME `id` $(id)",
+        "*;&|<>\\",
+        "",
+        "CHAVE=valor",
+    ];
+
+    let cmd = shell_join(args);
+    println!("\n{cmd}");
+    assert_eq!(sh_echo(&cmd), format!("{}|", args.join("|")));
+}
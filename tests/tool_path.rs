@@ -0,0 +1,27 @@
+use sandbox_utils::*;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_set_sandbox_tool_path() {
+    sandbox_init("ArchLinux", "ARCH").expect("Failed");
+
+    let dir = PathBuf::from("/tmp/test_tool_path");
+    fs::create_dir_all(&dir).expect("Failed");
+    let plain = dir.join("bwrap");
+    fs::write(&plain, "#!/bin/sh\n").expect("Failed");
+
+    assert!(set_sandbox_tool_path(USE_BWRAP, dir.join("inexistente")).is_err());
+    assert!(set_sandbox_tool_path(USE_BWRAP, dir.clone()).is_err());
+    let err = set_sandbox_tool_path(USE_BWRAP, plain).expect_err("Needs the exec bit");
+    assert!(err.to_string().contains("not an executable"), "{err}");
+    assert!(set_sandbox_tool_path("docker", PathBuf::from("/bin/sh")).is_err());
+
+    let vendored = resolve_tool(USE_BWRAP).expect("Test bwrap is missing");
+    set_sandbox_tool_path(USE_BWRAP, vendored.clone()).expect("Failed");
+    assert_eq!(sandbox_tool(), USE_BWRAP);
+    assert_eq!(tool_target(), vendored);
+
+    fs::remove_dir_all(&dir).expect("Failed");
+    println!("\x1b[1;32m--> Caminho da Ferramenta Passou!\x1b[0m");
+}
@@ -0,0 +1,56 @@
+use sandbox_utils::*;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::thread;
+
+#[test]
+fn test_tool_download_checks_pinned_digest() {
+    let base = PathBuf::from("/tmp/test_tool_pin");
+    let _ = fs::remove_dir_all(&base);
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let mirror = format!("http://{}", listener.local_addr().expect("Failed"));
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut request = [0u8; 4096];
+            let _ = stream.read(&mut request);
+            let body = b"#!/bin/sh\necho adulterado\n";
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(header.as_bytes());
+            let _ = stream.write_all(body);
+        }
+    });
+
+    unsafe {
+        std::env::set_var(TOOL_MIRROR_ENV, &mirror);
+        std::env::set_var("PATH", "/usr/bin:/bin");
+    }
+    sandbox_init_with(SandboxConfig {
+        app_name: "meuapp".into(),
+        app_arch: "x86_64".into(),
+        safe_home: base.join("home"),
+        config_dir: base.join("config"),
+        config_file: base.join("config/config.toml"),
+        default_cache: base.join("cache"),
+        default_rootfs: base.join("rootfs"),
+        temp_cache: base.join("tmp"),
+    })
+    .expect("Init failed");
+    set_tool_install_dir(base.join("bin"));
+
+    set_tool_sha256(USE_BWRAP, &"ab".repeat(32)).expect("Failed to pin");
+    assert!(tool_has_download(USE_BWRAP, "x86_64"));
+
+    let err = set_sandbox_tool(USE_BWRAP).expect_err("Unverified binary installed");
+    assert!(err.is::<ChecksumMismatchError>(), "{err}");
+    assert!(!base.join("bin/bwrap").exists());
+
+    fs::remove_dir_all(&base).expect("Failed");
+    println!("\x1b[1;32m--> Download Com SHA-256 Divergente Recusado Passou!\x1b[0m");
+}
@@ -0,0 +1,101 @@
+use sandbox_utils::*;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Serves a fake tool release, answering `304` when the `ETag` matches.
+fn serve_release(release: Arc<Mutex<(String, String)>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let url = format!("http://{}", listener.local_addr().expect("Failed"));
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.expect("Failed to accept");
+            let mut request = [0u8; 4096];
+            let n = stream.read(&mut request).unwrap_or(0);
+            let request = String::from_utf8_lossy(&request[..n]).to_lowercase();
+
+            let (etag, body) = release.lock().expect("Failed").clone();
+            let response = match request.contains(&format!("if-none-match: \"{etag}\"")) {
+                true => "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string(),
+                false => format!(
+                    "HTTP/1.1 200 OK\r\nETag: \"{etag}\"\r\nContent-Length: {}\r\n\
+                     Connection: close\r\n\r\n{body}",
+                    body.len()
+                ),
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    url
+}
+
+/// Builds a fake tool script printing a PRoot-like version banner, and pins
+/// its digest.
+fn release(version: &str) -> (String, String) {
+    let body = format!("#!/bin/sh\necho \"proot v{version}\"\n");
+    let digest = format!("{:x}", Sha256::digest(&body));
+    set_tool_sha256(USE_PROOT, &digest).expect("Failed to pin");
+    (version.to_string(), body)
+}
+
+#[test]
+fn test_tool_version_and_update() {
+    let base = PathBuf::from("/tmp/test_tool_update");
+    let _ = fs::remove_dir_all(&base);
+
+    let current = Arc::new(Mutex::new(release("5.4.0")));
+    let mirror = serve_release(current.clone());
+    unsafe {
+        std::env::set_var(TOOL_MIRROR_ENV, &mirror);
+        std::env::set_var("PATH", "/usr/bin:/bin");
+    }
+    sandbox_init_with(SandboxConfig {
+        app_name: "meuapp".into(),
+        app_arch: "x86_64".into(),
+        safe_home: base.join("home"),
+        config_dir: base.join("config"),
+        config_file: base.join("config/config.toml"),
+        default_cache: base.join("cache"),
+        default_rootfs: base.join("rootfs"),
+        temp_cache: base.join("tmp"),
+    })
+    .expect("Init failed");
+    set_tool_install_dir(base.join("bin"));
+
+    assert_eq!(tool_version(USE_PROOT), None);
+    set_sandbox_tool(USE_PROOT).expect("Install failed");
+    assert_eq!(tool_version(USE_PROOT).as_deref(), Some("5.4.0"));
+
+    assert!(
+        !update_tool(USE_PROOT, false).expect("Failed"),
+        "Same binary"
+    );
+    assert!(!update_tool(USE_PROOT, false).expect("Failed"), "304 reuse");
+
+    *current.lock().expect("Failed") = release("5.5.0");
+    assert!(
+        update_tool(USE_PROOT, false).expect("Failed"),
+        "New release"
+    );
+    assert_eq!(tool_version(USE_PROOT).as_deref(), Some("5.5.0"));
+
+    let published = release("5.6.0");
+    set_tool_sha256(USE_PROOT, &"0".repeat(64)).expect("Failed to pin");
+    *current.lock().expect("Failed") = published;
+    assert!(update_tool(USE_PROOT, false).is_err(), "Digest mismatch");
+    assert_eq!(tool_version(USE_PROOT).as_deref(), Some("5.5.0"));
+    release("5.6.0");
+    assert!(
+        update_tool(USE_PROOT, true).expect("Failed"),
+        "Forced update"
+    );
+
+    fs::remove_dir_all(&base).expect("Failed");
+    println!("\x1b[1;32m--> Versão e Atualização da Ferramenta Passou!\x1b[0m");
+}
@@ -0,0 +1,26 @@
+use sandbox_utils::*;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_xdg_base_dirs() {
+    let base = PathBuf::from("/tmp/test_xdg_dirs");
+    let _ = fs::remove_dir_all(&base);
+    unsafe {
+        env::set_var("HOME", base.join("home"));
+        env::set_var("XDG_CONFIG_HOME", base.join("config"));
+        env::set_var("XDG_CACHE_HOME", "relativo/cache");
+    }
+
+    sandbox_init("ArchLinux", "ARCH").expect("Init failed");
+
+    assert_eq!(config_dir(), base.join("config/ArchLinux"));
+    assert_eq!(config_file(), base.join("config/ArchLinux/config.toml"));
+    assert!(config_dir().is_dir());
+    // A relative XDG path is invalid per the spec and falls back to the home.
+    assert_eq!(default_cache(), base.join("home/.cache/ArchLinux"));
+    assert_eq!(default_rootfs(), base.join("home/.ArchLinux"));
+    fs::remove_dir_all(&base).expect("Failed");
+    println!("\x1b[1;32m--> Diretórios XDG Passou!\x1b[0m");
+}